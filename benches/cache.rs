@@ -0,0 +1,108 @@
+// Throughput of the sharded cache against the former single Mutex + Condvar
+// store, run with `cargo bench --bench cache`
+use std::{
+    collections::{HashMap, HashSet},
+    hint::black_box,
+    sync::{Condvar, Mutex},
+    thread,
+    time::Instant,
+};
+
+use gobblers::{
+    rng::Rng,
+    search::{cache::Cache, EvalKind, Evaluation},
+};
+
+const KEYS: u64 = 100_000;
+const OPS: usize = 200_000;
+
+trait Store: Sync {
+    fn begin(&self, id: u64) -> Option<Evaluation>;
+    fn finish(&self, id: u64, eval: Evaluation);
+}
+
+struct Single {
+    store: Mutex<(HashMap<u64, Evaluation>, HashSet<u64>)>,
+    cv: Condvar,
+}
+
+impl Store for Single {
+    fn begin(&self, id: u64) -> Option<Evaluation> {
+        let mut guard = self.store.lock().unwrap();
+        while guard.1.contains(&id) {
+            guard = self.cv.wait(guard).unwrap();
+        }
+        if let Some(e) = guard.0.get(&id) {
+            return Some(*e);
+        }
+        guard.1.insert(id);
+        return None;
+    }
+
+    fn finish(&self, id: u64, eval: Evaluation) {
+        let mut guard = self.store.lock().unwrap();
+        guard.0.insert(id, eval);
+        guard.1.remove(&id);
+        self.cv.notify_all();
+    }
+}
+
+impl Store for Cache {
+    fn begin(&self, id: u64) -> Option<Evaluation> {
+        return Cache::begin(self, id, |_| true);
+    }
+
+    fn finish(&self, id: u64, eval: Evaluation) {
+        Cache::finish(self, id, Some(eval));
+    }
+}
+
+fn run(store: &impl Store, threads: usize) -> f64 {
+    let eval = Evaluation {
+        kind: EvalKind::TooFar,
+        depth: 10,
+        time: 0.0,
+        nodes: 0,
+    };
+    let start = Instant::now();
+    thread::scope(|s| {
+        for t in 0..threads {
+            s.spawn(move || {
+                let mut rng = Rng::new(t as u64);
+                for _ in 0..OPS {
+                    let id = rng.next_u64() % KEYS;
+                    if store.begin(id).is_none() {
+                        // Stand-in for a short search
+                        let mut x = id;
+                        for _ in 0..200 {
+                            x = black_box(x.wrapping_mul(31).wrapping_add(7));
+                        }
+                        store.finish(id, eval);
+                    }
+                }
+            });
+        }
+    });
+    return (OPS * threads) as f64 / start.elapsed().as_secs_f64();
+}
+
+fn main() {
+    let max = thread::available_parallelism()
+        .map_or(4, |n| n.get())
+        .max(4);
+    let mut threads = 1;
+    while threads <= max {
+        let single = Single {
+            store: Mutex::new((HashMap::new(), HashSet::new())),
+            cv: Condvar::new(),
+        };
+        let sharded = Cache::new(HashMap::new());
+        println!(
+            "{:2} threads: mutex {:>12.0} ops/s, sharded {:>12.0} ops/s",
+            threads,
+            run(&single, threads),
+            run(&sharded, threads)
+        );
+        threads *= 2;
+    }
+}
@@ -0,0 +1,10 @@
+#![no_main]
+
+use gobblers::reference::differential;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|seed: u64| {
+    if let Err(d) = differential(seed, 1, 80) {
+        panic!("{}", d);
+    }
+});
@@ -0,0 +1,208 @@
+#![no_main]
+
+use std::{
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
+
+use arbitrary::Arbitrary;
+use gobblers::{
+    actions::TakebackPolicy,
+    clock::{ManualClock, TimeControl},
+    reference::check_game_board,
+    search::Search,
+    setup::Setup,
+    Branching, GameBoard, Move, State,
+};
+use libfuzzer_sys::fuzz_target;
+
+// Shared by all runs, so the advisor's cache and table are built once
+static SEARCH: OnceLock<Search> = OnceLock::new();
+
+// Changes to the position in setup mode, applied to the current position
+#[derive(Debug, Arbitrary)]
+enum Edit {
+    Place(i32, i32, i32),
+    Remove(i32, i32),
+    SetReserve(i32, i32, i32),
+    SetPlayer(i32),
+    Clear,
+}
+
+// Everything the UI can do to a GameBoard, with unchecked arguments
+#[derive(Debug, Arbitrary)]
+enum Call {
+    SelectBoard(i32),
+    SelectNew(i32),
+    Submit,
+    Remove,
+    DoMove(bool, i32, i32, i32),
+    Undo,
+    Redo,
+    GotoPly(usize),
+    GotoMainLine,
+    Promote,
+    Delete,
+    GetTop(i32),
+    GetLeft(i32, i32),
+    IsSelectedBoard(i32),
+    IsSelectedNew(i32),
+    MaxId,
+    // Times in milliseconds on a clock only `Tick` advances
+    SetClock(u16, u16, u16),
+    RemoveClock,
+    StartClock,
+    PauseClock,
+    Tick(u16),
+    CheckClock,
+    Resign(i32),
+    OfferDraw(i32),
+    AcceptDraw(i32),
+    DeclineDraw(i32),
+    TakebackPolicy(u8),
+    RequestTakeback(i32),
+    AcceptTakeback(i32),
+    DeclineTakeback(i32),
+    // Replaces the game by one starting from the edited position
+    Setup(Vec<Edit>),
+    // Hint and warning at a shallow depth
+    Advise(bool),
+}
+
+fuzz_target!(|input: (bool, bool, Vec<Call>)| {
+    let (auto_select, variations, calls) = input;
+    let branching = match variations {
+        true => Branching::Variation,
+        false => Branching::Truncate,
+    };
+    let mut g = GameBoard::new(auto_select);
+    g.branching = branching;
+    let source = Arc::new(ManualClock::new());
+    for call in calls {
+        match call {
+            Call::SelectBoard(pos) => {
+                g.select_board(pos);
+            }
+            Call::SelectNew(size) => {
+                g.select_new(size);
+            }
+            Call::Submit => {
+                g.submit_select();
+            }
+            Call::Remove => {
+                g.remove_select();
+            }
+            Call::DoMove(is_new, size, from, to) => {
+                let m = Move {
+                    is_new,
+                    size,
+                    from: if is_new { -1 } else { from },
+                    to,
+                };
+                let legal = g.get_state() == State::InGame && g.get_moves().contains(&m);
+                assert_eq!(g.do_move(Move { from, ..m }), legal, "{:?}", m);
+            }
+            Call::Undo => {
+                g.undo_move();
+            }
+            Call::Redo => {
+                g.redo_move();
+            }
+            Call::GotoPly(ply) => {
+                g.goto_ply(ply);
+            }
+            Call::GotoMainLine => g.goto_main_line(),
+            Call::Promote => g.promote_variation(),
+            Call::Delete => {
+                g.delete_variation();
+            }
+            Call::GetTop(pos) => {
+                g.get_top(pos);
+            }
+            Call::GetLeft(player, size) => {
+                g.get_left(player, size);
+            }
+            Call::IsSelectedBoard(pos) => {
+                g.is_selected_board(pos);
+            }
+            Call::IsSelectedNew(size) => {
+                g.is_selected_new(size);
+            }
+            Call::MaxId => {
+                g.get_max_id();
+            }
+            Call::SetClock(base, increment, delay) => {
+                let ms = |t| Duration::from_millis(t as u64);
+                let control = TimeControl::new(ms(base), ms(increment), ms(delay));
+                g.set_clock(control, source.clone());
+            }
+            Call::RemoveClock => g.remove_clock(),
+            Call::StartClock => g.start_clock(),
+            Call::PauseClock => g.pause_clock(),
+            Call::Tick(ms) => source.advance(Duration::from_millis(ms as u64)),
+            Call::CheckClock => {
+                g.check_clock();
+            }
+            Call::Resign(player) => {
+                g.resign(player);
+            }
+            Call::OfferDraw(player) => {
+                g.offer_draw(player);
+            }
+            Call::AcceptDraw(player) => {
+                g.accept_draw(player);
+            }
+            Call::DeclineDraw(player) => {
+                g.decline_draw(player);
+            }
+            Call::TakebackPolicy(policy) => {
+                g.takeback_policy = match policy % 3 {
+                    0 => TakebackPolicy::Never,
+                    1 => TakebackPolicy::OnRequest,
+                    _ => TakebackPolicy::Always,
+                };
+            }
+            Call::RequestTakeback(player) => {
+                g.request_takeback(player);
+            }
+            Call::AcceptTakeback(player) => {
+                g.accept_takeback(player);
+            }
+            Call::DeclineTakeback(player) => {
+                g.decline_takeback(player);
+            }
+            Call::Setup(edits) => {
+                let mut setup = Setup::from_game(&g);
+                for edit in edits {
+                    let _ = match edit {
+                        Edit::Place(player, size, pos) => setup.place(player, size, pos),
+                        Edit::Remove(size, pos) => setup.remove(size, pos).map(|_| ()),
+                        Edit::SetReserve(player, size, count) => {
+                            setup.set_reserve(player, size, count)
+                        }
+                        Edit::SetPlayer(player) => setup.set_player(player),
+                        Edit::Clear => {
+                            setup.clear();
+                            Ok(())
+                        }
+                    };
+                }
+                if let Ok(next) = setup.finish(auto_select) {
+                    g = next;
+                    g.branching = branching;
+                }
+            }
+            Call::Advise(check) => {
+                g.set_advisor(SEARCH.get_or_init(Search::empty).clone(), 2);
+                if check {
+                    g.check_select();
+                } else {
+                    g.hint();
+                }
+            }
+        }
+        if let Some(what) = check_game_board(&g) {
+            panic!("{} after {:?}", what, g.get_history());
+        }
+    }
+});
@@ -0,0 +1,22 @@
+#![no_main]
+
+use gobblers::{record::Record, reference::check_game_board, GameBoard};
+use libfuzzer_sys::fuzz_target;
+
+// Saved games come from disk, any bytes must load or be refused cleanly
+fuzz_target!(|data: &[u8]| {
+    let Ok(record) = bincode::deserialize::<Record>(data) else {
+        return;
+    };
+    for auto_select in [false, true] {
+        let Some(g) = GameBoard::from_record(record.clone(), auto_select) else {
+            continue;
+        };
+        if let Some(what) = check_game_board(&g) {
+            panic!("{} after loading {:?}", what, record);
+        }
+        let saved = g.to_record();
+        let again = GameBoard::from_record(saved.clone(), auto_select).unwrap();
+        assert_eq!(again.to_record(), saved);
+    }
+});
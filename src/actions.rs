@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{GameBoard, State};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Action {
+    Resign,
+    OfferDraw,
+    AcceptDraw,
+    DeclineDraw,
+    RequestTakeback,
+    AcceptTakeback,
+    DeclineTakeback,
+}
+
+// `player` did `action` when `ply` moves were played
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Event {
+    pub ply: usize,
+    pub player: i32,
+    pub action: Action,
+}
+
+// How a game ended other than by a line, with the player who lost
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ending {
+    Timeout(i32),
+    Resign(i32),
+    Agreement,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TakebackPolicy {
+    Never,
+    // The opponent has to accept
+    OnRequest,
+    Always,
+}
+
+impl GameBoard {
+    fn record_event(&mut self, player: i32, action: Action) {
+        self.events.push(Event {
+            ply: self.ply(),
+            player,
+            action,
+        });
+    }
+
+    // Actions only count for the players of a running game
+    fn can_act(&self, player: i32) -> bool {
+        return (0..2).contains(&player) && self.s == State::InGame;
+    }
+
+    pub fn get_events(&self) -> &[Event] {
+        return &self.events;
+    }
+
+    pub fn get_ending(&self) -> Option<Ending> {
+        if let Some(p) = self.clock.as_ref().and_then(|c| c.flagged()) {
+            return Some(Ending::Timeout(p));
+        }
+        return self.events.iter().find_map(|e| match e.action {
+            Action::Resign => Some(Ending::Resign(e.player)),
+            Action::AcceptDraw => Some(Ending::Agreement),
+            _ => None,
+        });
+    }
+
+    pub fn get_draw_offer(&self) -> Option<i32> {
+        return self.draw_offer;
+    }
+
+    pub fn get_takeback_request(&self) -> Option<i32> {
+        return self.takeback_request;
+    }
+
+    pub fn resign(&mut self, player: i32) -> bool {
+        if !self.can_act(player) {
+            return false;
+        }
+        self.record_event(player, Action::Resign);
+        self.draw_offer = None;
+        self.takeback_request = None;
+        self.update_state();
+        return true;
+    }
+
+    // Stands until the opponent answers or makes a move
+    pub fn offer_draw(&mut self, player: i32) -> bool {
+        if !self.can_act(player) || self.draw_offer.is_some() {
+            return false;
+        }
+        self.record_event(player, Action::OfferDraw);
+        self.draw_offer = Some(player);
+        return true;
+    }
+
+    pub fn accept_draw(&mut self, player: i32) -> bool {
+        if !self.can_act(player) || self.draw_offer != Some(player ^ 1) {
+            return false;
+        }
+        self.record_event(player, Action::AcceptDraw);
+        self.draw_offer = None;
+        self.takeback_request = None;
+        self.update_state();
+        return true;
+    }
+
+    pub fn decline_draw(&mut self, player: i32) -> bool {
+        if !self.can_act(player) || self.draw_offer != Some(player ^ 1) {
+            return false;
+        }
+        self.record_event(player, Action::DeclineDraw);
+        self.draw_offer = None;
+        return true;
+    }
+
+    // Asks to take back the last own move, under Always it happens right away
+    pub fn request_takeback(&mut self, player: i32) -> bool {
+        if !self.can_act(player)
+            || self.takeback_policy == TakebackPolicy::Never
+            || self.takeback_request.is_some()
+            || self.takeback_plies(player) > self.ply()
+        {
+            return false;
+        }
+        self.record_event(player, Action::RequestTakeback);
+        self.takeback_request = Some(player);
+        if self.takeback_policy == TakebackPolicy::Always {
+            return self.accept_takeback(player ^ 1);
+        }
+        return true;
+    }
+
+    pub fn accept_takeback(&mut self, player: i32) -> bool {
+        if !self.can_act(player) || self.takeback_request != Some(player ^ 1) {
+            return false;
+        }
+        let plies = self.takeback_plies(player ^ 1);
+        if plies > self.ply() {
+            return false;
+        }
+        self.record_event(player, Action::AcceptTakeback);
+        self.takeback_request = None;
+        // The moves taken back leave the record, so redo can't bring them
+        // back and the game goes on in the line they were played in
+        for _ in 1..plies {
+            self.undo_move();
+        }
+        self.delete_variation();
+        return true;
+    }
+
+    pub fn decline_takeback(&mut self, player: i32) -> bool {
+        if !self.can_act(player) || self.takeback_request != Some(player ^ 1) {
+            return false;
+        }
+        self.record_event(player, Action::DeclineTakeback);
+        self.takeback_request = None;
+        return true;
+    }
+
+    // Moves to undo until `player` is to move before their last move
+    fn takeback_plies(&self, player: i32) -> usize {
+        return match self.player() == player {
+            true => 2,
+            false => 1,
+        };
+    }
+}
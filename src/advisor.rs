@@ -0,0 +1,88 @@
+use std::fmt::Debug;
+
+use crate::{
+    bot::{candidates, Candidate},
+    search::{EvalKind, Search},
+    GameBoard, Select,
+};
+
+// Solver attached to a GameBoard for hints and warnings
+#[derive(Clone)]
+pub struct Advisor {
+    search: Search,
+    max_depth: i32,
+}
+
+// What the pending move of the selection gives away
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Warning {
+    // `best` wins, the pending move does not
+    MissesWin { best: Candidate },
+    // The pending move loses in `plies` though `best` does not lose
+    WalksIntoLoss { plies: u16, best: Candidate },
+}
+
+impl Debug for Advisor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Advisor")
+            .field("max_depth", &self.max_depth)
+            .finish()
+    }
+}
+
+// The search is only a tool, it is not part of the game
+impl PartialEq for Advisor {
+    fn eq(&self, other: &Self) -> bool {
+        return self.max_depth == other.max_depth;
+    }
+}
+
+impl Eq for Advisor {}
+
+impl GameBoard {
+    pub fn set_advisor(&mut self, search: Search, max_depth: i32) {
+        self.advisor = Some(Advisor { search, max_depth });
+    }
+
+    pub fn remove_advisor(&mut self) {
+        self.advisor = None;
+    }
+
+    fn candidates(&self) -> Vec<Candidate> {
+        let Some(advisor) = &self.advisor else {
+            return Vec::new();
+        };
+        return candidates(&mut advisor.search.clone(), self, advisor.max_depth);
+    }
+
+    // Best move with its outcome, None without advisor or moves
+    pub fn hint(&self) -> Option<Candidate> {
+        return self.candidates().first().copied();
+    }
+
+    // Checks the move waiting in Select::Move before submit_select commits it
+    pub fn check_select(&self) -> Option<Warning> {
+        let (sel, m) = self.get_select();
+        if sel != Select::Move {
+            return None;
+        }
+        let candidates = self.candidates();
+        let best = *candidates.first()?;
+        let pending = candidates.iter().find(|c| {
+            c.m.is_new == m.is_new
+                && c.m.size == m.size
+                && c.m.to == m.to
+                && (m.is_new || c.m.from == m.from)
+        })?;
+        if best.kind == EvalKind::Win && pending.kind != EvalKind::Win {
+            return Some(Warning::MissesWin { best });
+        }
+        if pending.kind == EvalKind::Loss && best.kind != EvalKind::Loss {
+            return Some(Warning::WalksIntoLoss {
+                plies: pending.plies,
+                best,
+            });
+        }
+        return None;
+    }
+}
@@ -0,0 +1,18 @@
+// Rewrites a scorebook keyed by the ids used before canonical ids in the
+// current format, see `search::migrate_scorebook`. `Search` migrates such
+// files when loading them too. Usage: migrate_scorebook <old> <new>
+use std::{env, io, process};
+
+use gobblers::search::{load_scorebook, save_scorebook};
+
+fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().collect();
+    let [_, old, new] = &args[..] else {
+        eprintln!("usage: migrate_scorebook <old> <new>");
+        process::exit(2);
+    };
+    let entries = load_scorebook(old)?;
+    save_scorebook(new, &entries)?;
+    println!("Migrated {} entries", entries.len());
+    return Ok(());
+}
@@ -0,0 +1,130 @@
+use crate::{
+    rng::Rng,
+    search::{EvalKind, Search},
+    GameBoard, Move, State,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Difficulty {
+    Beginner,
+    Easy,
+    Medium,
+    Hard,
+    Perfect,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BotConfig {
+    // Depth cap passed to `Search::evaluate` for every candidate
+    pub max_depth: i32,
+    // Chance to play a random non-losing move instead of the best one
+    pub blunder_rate: f32,
+}
+
+impl Difficulty {
+    pub fn config(self) -> BotConfig {
+        // Weak levels blunder often, but never walk into a loss they see
+        let (max_depth, blunder_rate) = match self {
+            Difficulty::Beginner => (2, 0.7),
+            Difficulty::Easy => (3, 0.4),
+            Difficulty::Medium => (5, 0.15),
+            Difficulty::Hard => (7, 0.05),
+            Difficulty::Perfect => (10, 0.0),
+        };
+        return BotConfig {
+            max_depth,
+            blunder_rate,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Candidate {
+    pub m: Move,
+    // Outcome for the player making the move
+    pub kind: EvalKind,
+    // Plies until the outcome, 0 if TooFar
+    pub plies: u16,
+}
+
+impl Candidate {
+    // Higher is better: fast wins, then draws and unknowns, then slow losses
+    fn rank(&self) -> i32 {
+        return match self.kind {
+            EvalKind::Win => 2000 - self.plies as i32,
+            EvalKind::TooFar => 1,
+            EvalKind::Draw => 0,
+            EvalKind::Loss => -2000 + self.plies as i32,
+        };
+    }
+}
+
+// All legal moves of `b` with their outcome, best first
+pub(crate) fn candidates(search: &mut Search, b: &GameBoard, max_depth: i32) -> Vec<Candidate> {
+    let mut candidates = Vec::new();
+    if b.get_state() != State::InGame {
+        return candidates;
+    }
+    let mut next = *b.get_board();
+    for m in b.get_moves() {
+        next.do_move(m);
+        let e = search.evaluate_board(&next, max_depth);
+        next.undo_move(m);
+        let (kind, plies) = match e.kind {
+            EvalKind::Win => (EvalKind::Loss, e.depth + 1),
+            EvalKind::Loss => (EvalKind::Win, e.depth + 1),
+            kind => (kind, 0),
+        };
+        candidates.push(Candidate { m, kind, plies });
+    }
+    candidates.sort_by_key(|c| -c.rank());
+    return candidates;
+}
+
+pub struct Bot {
+    search: Search,
+    config: BotConfig,
+    rng: Rng,
+}
+
+impl Bot {
+    pub fn new(search: Search, difficulty: Difficulty, seed: u64) -> Bot {
+        return Bot::with_config(search, difficulty.config(), seed);
+    }
+
+    pub fn with_config(search: Search, config: BotConfig, seed: u64) -> Bot {
+        return Bot {
+            search,
+            config,
+            rng: Rng::new(seed),
+        };
+    }
+
+    pub fn config(&self) -> BotConfig {
+        return self.config;
+    }
+
+    // All legal moves with their outcome, best first
+    pub fn candidates(&mut self, b: &GameBoard) -> Vec<Candidate> {
+        return candidates(&mut self.search, b, self.config.max_depth);
+    }
+
+    pub fn choose(&mut self, b: &GameBoard) -> Option<Move> {
+        let candidates = self.candidates(b);
+        if candidates.is_empty() {
+            return None;
+        }
+        let best = candidates[0].rank();
+        let pool: Vec<&Candidate> = match self.rng.chance(self.config.blunder_rate) {
+            true => candidates
+                .iter()
+                .filter(|c| c.kind != EvalKind::Loss)
+                .collect(),
+            false => candidates.iter().filter(|c| c.rank() == best).collect(),
+        };
+        if pool.is_empty() {
+            return Some(candidates[0].m);
+        }
+        return Some(pool[self.rng.below(pool.len())].m);
+    }
+}
@@ -0,0 +1,238 @@
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+// Where clocks read the time from, tests use ManualClock
+pub trait ClockSource: Send + Sync {
+    fn now(&self) -> Duration;
+}
+
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        return SystemClock {
+            start: Instant::now(),
+        };
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl ClockSource for SystemClock {
+    fn now(&self) -> Duration {
+        return self.start.elapsed();
+    }
+}
+
+// Only moves when told to
+#[derive(Default)]
+pub struct ManualClock {
+    now: Mutex<Duration>,
+}
+
+impl ManualClock {
+    pub fn new() -> ManualClock {
+        return ManualClock::default();
+    }
+
+    pub fn advance(&self, d: Duration) {
+        *self.now.lock().unwrap() += d;
+    }
+
+    pub fn set(&self, now: Duration) {
+        *self.now.lock().unwrap() = now;
+    }
+}
+
+impl ClockSource for ManualClock {
+    fn now(&self) -> Duration {
+        return *self.now.lock().unwrap();
+    }
+}
+
+// Increment is added after every move, during the delay at the start of a
+// turn the clock does not run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeControl {
+    pub base: Duration,
+    pub increment: Duration,
+    pub delay: Duration,
+}
+
+impl TimeControl {
+    pub fn new(base: Duration, increment: Duration, delay: Duration) -> TimeControl {
+        return TimeControl {
+            base,
+            increment,
+            delay,
+        };
+    }
+}
+
+// What is saved with a game, a loaded clock starts paused
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClockState {
+    pub control: TimeControl,
+    pub left: [Duration; 2],
+    pub flagged: Option<i32>,
+}
+
+#[derive(Clone)]
+pub struct Clock {
+    state: ClockState,
+    turn: i32,
+    // Source time the running turn was last charged at, None while paused
+    started: Option<Duration>,
+    // Delay not used up yet in the current turn
+    delay_left: Duration,
+    source: Arc<dyn ClockSource>,
+}
+
+impl Clock {
+    pub fn new(control: TimeControl, source: Arc<dyn ClockSource>) -> Clock {
+        let state = ClockState {
+            control,
+            left: [control.base; 2],
+            flagged: None,
+        };
+        return Clock::from_state(state, 0, source);
+    }
+
+    pub fn from_state(state: ClockState, turn: i32, source: Arc<dyn ClockSource>) -> Clock {
+        return Clock {
+            state,
+            turn,
+            started: None,
+            delay_left: state.control.delay,
+            source,
+        };
+    }
+
+    pub fn set_source(&mut self, source: Arc<dyn ClockSource>) {
+        self.charge();
+        self.source = source;
+        if self.started.is_some() {
+            self.started = Some(self.source.now());
+        }
+    }
+
+    pub fn control(&self) -> TimeControl {
+        return self.state.control;
+    }
+
+    // Player whose clock runs, or would run
+    pub fn turn(&self) -> i32 {
+        return self.turn;
+    }
+
+    pub fn is_running(&self) -> bool {
+        return self.started.is_some();
+    }
+
+    pub fn flagged(&self) -> Option<i32> {
+        return self.state.flagged;
+    }
+
+    // Zero for anyone but players 0 and 1
+    pub fn remaining(&self, player: i32) -> Duration {
+        if !(0..2).contains(&player) {
+            return Duration::ZERO;
+        }
+        let left = self.state.left[player as usize];
+        return match self.started {
+            Some(started) if player == self.turn => left.saturating_sub(self.spent(started)),
+            _ => left,
+        };
+    }
+
+    pub fn start(&mut self) {
+        if self.started.is_none() && self.state.flagged.is_none() {
+            self.started = Some(self.source.now());
+        }
+    }
+
+    pub fn pause(&mut self) {
+        self.charge();
+        self.started = None;
+    }
+
+    // Running time charged, ready to be saved
+    pub fn state(&self) -> ClockState {
+        let mut state = self.state;
+        state.left[self.turn as usize] = self.remaining(self.turn);
+        return state;
+    }
+
+    // Stops the clock once the player to move is out of time
+    pub fn check_flag(&mut self) -> Option<i32> {
+        if self.started.is_some() && self.remaining(self.turn).is_zero() {
+            self.pause();
+            self.state.flagged = Some(self.turn);
+        }
+        return self.state.flagged;
+    }
+
+    fn spent(&self, started: Duration) -> Duration {
+        let elapsed = self.source.now().saturating_sub(started);
+        return elapsed.saturating_sub(self.delay_left);
+    }
+
+    fn charge(&mut self) {
+        if let Some(started) = self.started {
+            self.state.left[self.turn as usize] = self.remaining(self.turn);
+            let elapsed = self.source.now().saturating_sub(started);
+            self.delay_left = self.delay_left.saturating_sub(elapsed);
+            self.started = Some(self.source.now());
+        }
+    }
+
+    // Hands the clock over to `turn`
+    pub(crate) fn switch(&mut self, turn: i32) {
+        if turn != self.turn {
+            self.charge();
+            self.turn = turn;
+            self.delay_left = self.state.control.delay;
+        }
+    }
+
+    pub(crate) fn add_increment(&mut self, player: i32) {
+        if self.started.is_some() {
+            let left = &mut self.state.left[player as usize];
+            *left = left.saturating_add(self.state.control.increment);
+        }
+    }
+}
+
+impl Debug for Clock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Clock")
+            .field("state", &self.state)
+            .field("turn", &self.turn)
+            .field("started", &self.started)
+            .field("delay_left", &self.delay_left)
+            .finish()
+    }
+}
+
+// The source is not part of the clock's state
+impl PartialEq for Clock {
+    fn eq(&self, other: &Self) -> bool {
+        return self.state == other.state
+            && self.turn == other.turn
+            && self.started == other.started
+            && self.delay_left == other.delay_left;
+    }
+}
+
+impl Eq for Clock {}
@@ -0,0 +1,82 @@
+use std::{fmt::Debug, sync::Arc};
+
+use crate::{GameBoard, Move, Select, State};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameEvent {
+    // Also sent when redo or goto steps forward
+    MoveMade(Move),
+    MoveUndone(Move),
+    // State from the view of the side to move
+    GameOver(State),
+    SelectionChanged(Select, Move),
+}
+
+pub type Observer = dyn Fn(&GameEvent) + Send + Sync;
+
+// Holds the observer and the last selection it was told about. A clone of
+// the board starts without observer, so copies for analysis stay quiet.
+#[derive(Default)]
+pub(crate) struct ObserverSlot {
+    observer: Option<Arc<Observer>>,
+    sel: Option<(Select, Move)>,
+}
+
+impl Clone for ObserverSlot {
+    fn clone(&self) -> Self {
+        return ObserverSlot::default();
+    }
+}
+
+impl Debug for ObserverSlot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ObserverSlot")
+            .field("observer", &self.observer.is_some())
+            .finish()
+    }
+}
+
+impl PartialEq for ObserverSlot {
+    fn eq(&self, _: &Self) -> bool {
+        return true;
+    }
+}
+
+impl Eq for ObserverSlot {}
+
+impl GameBoard {
+    // Called on every change, like `Search::set_progress`
+    pub fn set_observer(&mut self, observer: impl Fn(&GameEvent) + Send + Sync + 'static) {
+        self.observer = ObserverSlot {
+            observer: Some(Arc::new(observer)),
+            sel: Some(self.get_select()),
+        };
+    }
+
+    pub fn clear_observer(&mut self) {
+        self.observer = ObserverSlot::default();
+    }
+
+    pub(crate) fn emit(&self, e: GameEvent) {
+        if let Some(observer) = &self.observer.observer {
+            observer(&e);
+        }
+    }
+
+    // Tells the observer about the selection if it differs from the last one
+    pub(crate) fn notify_selection(&mut self) {
+        if self.observer.observer.is_none() {
+            return;
+        }
+        let (sel, m) = self.get_select();
+        let changed = match self.observer.sel {
+            Some((Select::None, _)) => sel != Select::None,
+            Some(last) => last != (sel, m),
+            None => true,
+        };
+        if changed {
+            self.observer.sel = Some((sel, m));
+            self.emit(GameEvent::SelectionChanged(sel, m));
+        }
+    }
+}
@@ -1,8 +1,30 @@
+pub mod actions;
+pub mod advisor;
+pub mod bot;
+pub mod clock;
+pub mod events;
+pub mod mcts;
+pub mod metadata;
+pub mod perft;
+pub mod positions;
+pub mod record;
+pub mod reference;
+pub mod rng;
 pub mod search;
+pub mod setup;
 
 use std::fmt::{Debug, Write};
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+use std::sync::Arc;
+
+use actions::{Ending, Event, TakebackPolicy};
+use advisor::Advisor;
+use clock::{Clock, ClockSource, TimeControl};
+use events::{GameEvent, ObserverSlot};
+use metadata::Metadata;
+use record::Node;
+
+#[derive(Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct Board {
     pub layers: [i32; 6],
     pub pieces: [i32; 6],
@@ -27,12 +49,12 @@ impl Board {
         spots |= (view << 3) & (view << 6) & 0b111000000; // Top spots vertical
         spots |= (view >> 3) & (view << 3) & 0b000111000; // Mid
         spots |= (view >> 6) & (view >> 3) & 0b000000111; // Bot
-        spots |= (view << 2) & (view << 4) & 0b000000100; // Top spots diag
+        spots |= (view >> 2) & (view >> 4) & 0b000000100; // Top spots diag
         spots |= (view >> 2) & (view << 2) & 0b000010000; // Mid
-        spots |= (view >> 4) & (view >> 2) & 0b001000000; // Bot
-        spots |= (view << 4) & (view << 8) & 0b000000001; // Top spots diag
+        spots |= (view << 4) & (view << 2) & 0b001000000; // Bot
+        spots |= (view >> 4) & (view >> 8) & 0b000000001; // Top spots diag
         spots |= (view >> 4) & (view << 4) & 0b000010000; // Mid
-        spots |= (view >> 8) & (view >> 4) & 0b100000000; // Bot
+        spots |= (view << 8) & (view << 4) & 0b100000000; // Bot
         return spots & (1 << pos) != 0;
     }
 
@@ -40,8 +62,9 @@ impl Board {
         return self.pieces[self.idx(size)] > 0;
     }
 
-    pub fn is_free(self: &Board, size: i32, pos: i32) -> bool {
-        let same = match size {
+    // Cells holding a piece of `size` or bigger
+    pub fn get_covering(&self, size: i32) -> i32 {
+        return match size {
             0 => {
                 self.layers[0]
                     | self.layers[3]
@@ -52,19 +75,27 @@ impl Board {
             }
             1 => self.layers[1] | self.layers[4] | self.layers[2] | self.layers[5],
             2 => self.layers[2] | self.layers[5],
+            3 => 0,
             _ => unreachable!(),
         };
-        return ((1 << pos) & same) == 0;
+    }
+
+    pub fn is_free(self: &Board, size: i32, pos: i32) -> bool {
+        return ((1 << pos) & self.get_covering(size)) == 0;
     }
 
     pub fn is_movable(self: &Board, size: i32, pos: i32) -> bool {
-        let bigger = match size {
-            0 => self.layers[1] | self.layers[4] | self.layers[2] | self.layers[5],
-            1 => self.layers[2] | self.layers[5],
-            2 => 0,
-            _ => unreachable!(),
-        };
-        return ((1 << pos) & self.layers[self.idx(size)] & !bigger) != 0;
+        return ((1 << pos) & self.get_sources(size)) != 0;
+    }
+
+    // Cells a piece of `size` can be put on
+    pub fn get_targets(&self, size: i32) -> i32 {
+        return !self.get_covering(size) & 0b111111111;
+    }
+
+    // Cells where the player to move has a piece of `size` on top
+    pub fn get_sources(&self, size: i32) -> i32 {
+        return self.layers[self.idx(size)] & !self.get_covering(size + 1);
     }
 
     pub fn get_view(&self, player: i32) -> i32 {
@@ -100,6 +131,11 @@ impl Board {
         return ((1 << pos) & smaller) != 0;
     }
 
+    // Every piece not on the board is in reserve, ids don't tell otherwise
+    pub fn has_full_reserves(&self) -> bool {
+        return (0..6).all(|i| self.pieces[i] + self.layers[i].count_ones() as i32 == 2);
+    }
+
     pub fn idx(&self, size: i32) -> usize {
         return (self.player * 3 + size) as usize;
     }
@@ -127,6 +163,111 @@ impl Board {
         self.layers[self.idx(size)] ^= 1 << to;
         self.layers[self.idx(size)] |= 1 << from;
     }
+
+    pub fn do_move(&mut self, m: Move) {
+        match m.is_new {
+            true => self.do_new_move(m.size, m.to),
+            false => self.do_board_move(m.size, m.from, m.to),
+        }
+    }
+
+    pub fn undo_move(&mut self, m: Move) {
+        match m.is_new {
+            true => self.undo_new_move(m.size, m.to),
+            false => self.undo_board_move(m.size, m.from, m.to),
+        }
+    }
+
+    // Layers and player, the pieces left follow from the layers
+    pub fn get_id(&self) -> u64 {
+        let mut id: u64 = 0;
+        for i in 0..6 {
+            id <<= 9;
+            id |= self.layers[i] as u64;
+        }
+        id <<= 1;
+        id |= self.player as u64;
+        return id;
+    }
+
+    pub fn from_id(mut id: u64) -> Board {
+        let mut b = Board::new();
+        b.player = (id & 1) as i32;
+        id >>= 1;
+        for i in (0..6).rev() {
+            b.layers[i] = (id & 0b111111111) as i32;
+            b.pieces[i] = 2 - b.layers[i].count_ones() as i32;
+            id >>= 9;
+        }
+        return b;
+    }
+
+    fn reorder(&mut self, order: [usize; 9]) {
+        let mut tmp = [0; 6];
+        for (i, layer) in self.layers.iter().enumerate() {
+            for (pos, to) in order.iter().enumerate() {
+                if layer & (1 << pos) != 0 {
+                    tmp[i] |= 1 << to;
+                }
+            }
+        }
+        self.layers = tmp;
+    }
+
+    // Largest id among the 8 rotations and mirrors, equal for all
+    // symmetric positions
+    pub fn get_max_id(&self) -> u64 {
+        let rotate = [2, 5, 8, 1, 4, 7, 0, 3, 6];
+        let mirror = [6, 7, 8, 3, 4, 5, 0, 1, 2];
+        let mut tmp = *self;
+        let mut max_id = 0;
+        for _ in 0..2 {
+            for _ in 0..4 {
+                max_id = max_id.max(tmp.get_id());
+                tmp.reorder(rotate);
+            }
+            tmp.reorder(mirror);
+        }
+        return max_id;
+    }
+
+    pub fn get_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        for to in 0..9 {
+            for size in 0..3 {
+                if !self.is_free(size, to) {
+                    continue;
+                }
+                for from in 0..9 {
+                    if !self.is_movable(size, from) {
+                        continue;
+                    }
+                    moves.push(Move {
+                        is_new: false,
+                        size,
+                        from,
+                        to,
+                    });
+                }
+                if !self.is_left(size) {
+                    continue;
+                }
+                moves.push(Move {
+                    is_new: true,
+                    size,
+                    from: -1,
+                    to,
+                });
+            }
+        }
+        return moves;
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        return Self::new();
+    }
 }
 
 impl Debug for Board {
@@ -170,7 +311,7 @@ pub enum State {
     Loss,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub struct Move {
     pub is_new: bool,
     pub size: i32,
@@ -185,20 +326,82 @@ pub enum Select {
     Move,
 }
 
+// What a selection call did, the Move is the selection afterwards
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SelectionEvent {
+    // Nothing is dropped yet, `to` is -1
+    PickedUp(Move),
+    // Waits for submit_select
+    Dropped(Move),
+    Committed(Move),
+    Deselected,
+    Invalid(Rejection),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Rejection {
+    GameOver,
+    // Position or size that does not exist
+    OffBoard,
+    Empty,
+    NotYourPiece,
+    NoneLeft,
+    // Target holds a piece of the same size or bigger
+    Covered,
+    NothingSelected,
+    NothingDropped,
+}
+
+// What a new move does when the cursor is not at the end of the line
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Branching {
+    // Drop the rest of the line
+    Truncate,
+    // Keep the main line and play on in a variation
+    Variation,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GameBoard {
+    // Moves played to reach the current position
     history: Vec<Move>,
+    // Position the game started from
+    start: Board,
+    tree: Node,
+    // Child index for every ply of the current line, also after the cursor
+    line: Vec<usize>,
     b: Board,
     s: State,
     sel: Select,
     m: Move,
+    clock: Option<Clock>,
+    events: Vec<Event>,
+    draw_offer: Option<i32>,
+    takeback_request: Option<i32>,
+    advisor: Option<Advisor>,
+    observer: ObserverSlot,
+    metadata: Metadata,
     pub auto_select: bool,
+    pub branching: Branching,
+    pub takeback_policy: TakebackPolicy,
+}
+
+// GameBoard takes positions and sizes from the UI, anything outside the board is ignored
+fn is_pos(pos: i32) -> bool {
+    return (0..9).contains(&pos);
+}
+
+fn is_size(size: i32) -> bool {
+    return (0..3).contains(&size);
 }
 
 impl GameBoard {
     pub fn new(auto_select: bool) -> GameBoard {
         return GameBoard {
             history: Vec::new(),
+            start: Board::new(),
+            tree: Node::root(),
+            line: Vec::new(),
             b: Board::new(),
             s: State::InGame,
             sel: Select::None,
@@ -209,6 +412,15 @@ impl GameBoard {
                 to: 0,
             },
             auto_select,
+            clock: None,
+            events: Vec::new(),
+            draw_offer: None,
+            takeback_request: None,
+            advisor: None,
+            observer: ObserverSlot::default(),
+            metadata: Metadata::default(),
+            branching: Branching::Truncate,
+            takeback_policy: TakebackPolicy::OnRequest,
         };
     }
 
@@ -220,44 +432,8 @@ impl GameBoard {
         return &self.b;
     }
 
-    fn get_id(&self) -> u64 {
-        let mut id: u64 = 0;
-        for i in 0..6 {
-            id <<= 9;
-            id |= self.b.layers[i] as u64;
-        }
-        id <<= 8;
-        id |= self.history.len() as u64;
-        id <<= 1;
-        id |= self.b.player as u64;
-        return id;
-    }
-
-    fn reorder(&mut self, order: [usize; 9]) {
-        let mut tmp = [0; 6];
-        for i in 0..6 {
-            for pos in 0..9 {
-                if self.b.layers[i] & (1 << pos) != 0 {
-                    tmp[i] |= 1 << order[pos];
-                }
-            }
-        }
-        self.b.layers = tmp;
-    }
-
     pub fn get_max_id(&self) -> u64 {
-        let rotate = [2, 5, 8, 1, 4, 7, 0, 3, 6];
-        let mirror = [6, 7, 8, 3, 4, 5, 0, 1, 2];
-        let mut tmp = self.clone();
-        let mut max_id = 0;
-        for _ in 0..2 {
-            for _ in 0..4 {
-                max_id = self.get_id();
-                tmp.reorder(rotate);
-            }
-            tmp.reorder(mirror);
-        }
-        return max_id;
+        return self.b.get_max_id();
     }
 
     pub fn get_state(&self) -> State {
@@ -269,11 +445,17 @@ impl GameBoard {
     }
 
     pub fn get_left(&self, player: i32, size: i32) -> i32 {
-        return self.b.pieces[(player*3 + size) as usize];
+        if !(0..2).contains(&player) || !is_size(size) {
+            return 0;
+        }
+        return self.b.pieces[(player * 3 + size) as usize];
     }
 
     // Player + Size
     pub fn get_top(&self, pos: i32) -> Option<(i32, i32)> {
+        if !is_pos(pos) {
+            return None;
+        }
         for size in (0..3).rev() {
             for p in 0..2 {
                 if self.b.layers[(p * 3 + size) as usize] & (1 << pos) != 0 {
@@ -285,6 +467,9 @@ impl GameBoard {
     }
 
     pub fn is_valid(&self, m: Move) -> bool {
+        if !is_size(m.size) || !is_pos(m.to) || (!m.is_new && !is_pos(m.from)) {
+            return false;
+        }
         let from_ok = match m.is_new {
             true => self.b.is_left(m.size),
             false => self.b.is_movable(m.size, m.from),
@@ -293,6 +478,7 @@ impl GameBoard {
     }
 
     fn update_state(&mut self) {
+        let was = self.s;
         self.sel = Select::None;
         match self.b.get_state() {
             0 => self.s = State::InGame,
@@ -301,47 +487,143 @@ impl GameBoard {
             3 => self.s = State::Draw,
             _ => unreachable!(),
         }
+        match self.get_ending() {
+            Some(Ending::Agreement) => self.s = State::Draw,
+            Some(Ending::Timeout(p) | Ending::Resign(p)) => {
+                self.s = match p == self.b.player {
+                    true => State::Loss,
+                    false => State::Win,
+                }
+            }
+            None => {}
+        }
+        if let Some(clock) = &mut self.clock {
+            clock.switch(self.b.player);
+            if self.s != State::InGame {
+                clock.pause();
+            }
+        }
+        self.track_result();
+        self.notify_selection();
+        if was == State::InGame && self.s != State::InGame {
+            self.emit(GameEvent::GameOver(self.s));
+        }
+    }
+
+    // Clicking a cell picks up the own piece on it, with a piece picked up
+    // it drops it there, or commits the move with auto_select
+    pub fn select_board(&mut self, pos: i32) -> SelectionEvent {
+        let e = self.pick_board(pos);
+        self.notify_selection();
+        return e;
     }
 
-    pub fn select_board(&mut self, pos: i32) -> bool {
+    fn pick_board(&mut self, pos: i32) -> SelectionEvent {
         if self.s != State::InGame {
-            return false;
+            return SelectionEvent::Invalid(Rejection::GameOver);
         }
-        if self.sel == Select::None {
-            if let Some((p, s)) = self.get_top(pos) {
-                if p == self.b.player {
-                    self.sel = Select::From;
-                    self.m.is_new = false;
-                    self.m.size = s;
-                    self.m.from = pos;
-                }
-            }
-        } else {
-            if self.b.is_free(self.m.size, pos) {
-                self.sel = Select::Move;
-                self.m.to = pos;
-                if self.auto_select {
-                    self.submit_select();
-                }
-                return true;
-            } else {
-                self.sel = Select::None;
+        if !is_pos(pos) {
+            return SelectionEvent::Invalid(Rejection::OffBoard);
+        }
+        if self.sel != Select::None && !self.m.is_new && self.m.from == pos {
+            self.sel = Select::None;
+            return SelectionEvent::Deselected;
+        }
+        if self.sel != Select::None && self.b.is_free(self.m.size, pos) {
+            self.sel = Select::Move;
+            self.m.to = pos;
+            if self.auto_select {
+                return self.submit_select();
             }
+            return SelectionEvent::Dropped(self.m);
         }
-        return false;
+        return match self.get_top(pos) {
+            Some((p, size)) if p == self.b.player => {
+                self.sel = Select::From;
+                self.m.is_new = false;
+                self.m.size = size;
+                self.m.from = pos;
+                self.m.to = -1;
+                SelectionEvent::PickedUp(self.m)
+            }
+            _ if self.sel != Select::None => SelectionEvent::Invalid(Rejection::Covered),
+            Some(_) => SelectionEvent::Invalid(Rejection::NotYourPiece),
+            None => SelectionEvent::Invalid(Rejection::Empty),
+        };
+    }
+
+    // Picks up a new piece of the side to move, again drops it back
+    pub fn select_new(&mut self, size: i32) -> SelectionEvent {
+        let e = self.pick_new(size);
+        self.notify_selection();
+        return e;
     }
 
-    pub fn select_new(&mut self, player: i32, size: i32) {
+    fn pick_new(&mut self, size: i32) -> SelectionEvent {
         if self.s != State::InGame {
-            return;
+            return SelectionEvent::Invalid(Rejection::GameOver);
+        }
+        if !is_size(size) {
+            return SelectionEvent::Invalid(Rejection::OffBoard);
         }
-        if self.b.player == player && self.b.is_left(size) {
-            self.sel = Select::From;
-            self.m.is_new = true;
-            self.m.size = size;
-        } else {
+        if self.is_selected_new(size) {
             self.sel = Select::None;
+            return SelectionEvent::Deselected;
         }
+        if !self.b.is_left(size) {
+            return SelectionEvent::Invalid(Rejection::NoneLeft);
+        }
+        self.sel = Select::From;
+        self.m.is_new = true;
+        self.m.size = size;
+        self.m.from = -1;
+        self.m.to = -1;
+        return SelectionEvent::PickedUp(self.m);
+    }
+
+    // Cells the selected piece can go to, as a bitmask
+    pub fn get_select_targets(&self) -> i32 {
+        return match (self.sel, self.m.is_new) {
+            (Select::None, _) => 0,
+            (_, true) => self.legal_targets_new(self.m.size),
+            (_, false) => self.legal_targets(self.m.from),
+        };
+    }
+
+    // Where the piece on top of `from` can go, 0 if it's not the mover's
+    pub fn legal_targets(&self, from: i32) -> i32 {
+        if self.s != State::InGame || !is_pos(from) {
+            return 0;
+        }
+        return match self.get_top(from) {
+            Some((p, size)) if p == self.b.player => self.b.get_targets(size),
+            _ => 0,
+        };
+    }
+
+    pub fn legal_targets_new(&self, size: i32) -> i32 {
+        if self.s != State::InGame || !is_size(size) || !self.b.is_left(size) {
+            return 0;
+        }
+        return self.b.get_targets(size);
+    }
+
+    // Cells and reserve sizes the side to move has a legal move from
+    pub fn movable_sources(&self) -> (i32, i32) {
+        let (mut cells, mut sizes) = (0, 0);
+        if self.s != State::InGame {
+            return (cells, sizes);
+        }
+        for size in 0..3 {
+            if self.b.get_targets(size) == 0 {
+                continue;
+            }
+            cells |= self.b.get_sources(size);
+            if self.b.is_left(size) {
+                sizes |= 1 << size;
+            }
+        }
+        return (cells, sizes);
     }
 
     pub fn get_select(&self) -> (Select, Move) {
@@ -349,94 +631,334 @@ impl GameBoard {
     }
 
     pub fn is_selected_board(&self, pos: i32) -> bool {
-        if self.sel != Select::None && !self.m.is_new {
-            if self.m.from == pos {
-                return true;
-            }
+        if self.sel != Select::None && !self.m.is_new && self.m.from == pos {
+            return true;
         }
-        if self.sel == Select::Move {
-            if self.m.to == pos {
-                return true;
-            }
+        if self.sel == Select::Move && self.m.to == pos {
+            return true;
         }
         return false;
     }
 
     pub fn is_selected_new(&self, size: i32) -> bool {
-        if self.sel != Select::None && self.m.is_new {
-            if self.m.size == size {
-                return true;
-            }
+        if self.sel != Select::None && self.m.is_new && self.m.size == size {
+            return true;
         }
         return false;
-    }    
+    }
 
-    pub fn submit_select(&mut self) -> bool {
-        if self.sel == Select::Move {
-            self.do_move(self.m);
-            return true;
+    pub fn submit_select(&mut self) -> SelectionEvent {
+        if self.sel != Select::Move {
+            return SelectionEvent::Invalid(Rejection::NothingDropped);
         }
-        return false;
+        let m = self.m;
+        if !self.do_move(m) {
+            return SelectionEvent::Invalid(Rejection::GameOver);
+        }
+        return SelectionEvent::Committed(self.get_history()[self.ply() - 1]);
     }
 
-    pub fn remove_select(&mut self) {
+    pub fn remove_select(&mut self) -> SelectionEvent {
+        if self.sel == Select::None {
+            return SelectionEvent::Invalid(Rejection::NothingSelected);
+        }
         self.sel = Select::None;
+        self.notify_selection();
+        return SelectionEvent::Deselected;
     }
 
     pub fn do_move(&mut self, m: Move) -> bool {
+        self.check_clock();
         if !self.is_valid(m) || self.s != State::InGame {
             return false;
         }
-        match m.is_new {
-            true => self.b.do_new_move(m.size, m.to),
-            false => self.b.do_board_move(m.size, m.from, m.to),
+        // New moves come from nowhere, keep history comparable with get_moves
+        let m = match m.is_new {
+            true => Move { from: -1, ..m },
+            false => m,
         };
-        self.history.push(m);
-        self.update_state();
+        let ply = self.ply();
+        let next = self.line.get(ply).copied();
+        let branching = self.branching;
+        let node = self.node_mut(ply);
+        let idx = match node.children.iter().position(|c| c.m == Some(m)) {
+            Some(idx) if Some(idx) == next => return self.play_next(),
+            Some(idx) => idx,
+            None => match (branching, next) {
+                (Branching::Truncate, Some(next)) => {
+                    node.children[next] = Node::new(m);
+                    next
+                }
+                _ => {
+                    node.children.push(Node::new(m));
+                    node.children.len() - 1
+                }
+            },
+        };
+        self.line.truncate(ply);
+        self.line.push(idx);
+        self.extend_line();
+        return self.play_next();
+    }
+
+    // Plays the next move of the line as a real move, the mover gets the increment
+    fn play_next(&mut self) -> bool {
+        let player = self.b.player;
+        self.redo_move();
+        if let Some(clock) = &mut self.clock {
+            clock.add_increment(player);
+        }
+        // A move answers what the opponent asked for
+        if self.draw_offer == Some(player ^ 1) {
+            self.draw_offer = None;
+        }
+        if self.takeback_request == Some(player ^ 1) {
+            self.takeback_request = None;
+        }
         return true;
     }
 
+    // Steps back, the move stays in the line for redo_move
     pub fn undo_move(&mut self) -> bool {
         let Some(m) = self.history.pop() else {
             return false;
         };
-        match m.is_new {
-            true => self.b.undo_new_move(m.size, m.to),
-            false => self.b.undo_board_move(m.size, m.from, m.to),
-        }
+        self.b.undo_move(m);
+        self.emit(GameEvent::MoveUndone(m));
         self.update_state();
         return true;
     }
 
-    pub fn get_moves(&self) -> Vec<Move> {
+    pub fn redo_move(&mut self) -> bool {
+        let ply = self.ply();
+        if ply >= self.line.len() {
+            return false;
+        }
+        let m = self.node(ply + 1).m.unwrap();
+        self.replay(m);
+        return true;
+    }
+
+    // Plays `m`, the next move of the line
+    fn replay(&mut self, m: Move) {
+        self.b.do_move(m);
+        self.history.push(m);
+        self.emit(GameEvent::MoveMade(m));
+        self.update_state();
+    }
+
+    // Node reached after the first `ply` moves of the line
+    fn node(&self, ply: usize) -> &Node {
+        let mut node = &self.tree;
+        for &idx in &self.line[..ply] {
+            node = &node.children[idx];
+        }
+        return node;
+    }
+
+    fn node_mut(&mut self, ply: usize) -> &mut Node {
+        let mut node = &mut self.tree;
+        for &idx in &self.line[..ply] {
+            node = &mut node.children[idx];
+        }
+        return node;
+    }
+
+    // Follows the main continuation from the end of the line
+    fn extend_line(&mut self) {
+        let mut node = self.node(self.line.len());
+        let mut plies = 0;
+        while let Some(next) = node.children.first() {
+            node = next;
+            plies += 1;
+        }
+        self.line.resize(self.line.len() + plies, 0);
+    }
+
+    // Number of moves played to reach the current position
+    pub fn ply(&self) -> usize {
+        return self.history.len();
+    }
+
+    pub fn line_len(&self) -> usize {
+        return self.line.len();
+    }
+
+    // All moves of the current line, also the ones after the cursor
+    pub fn get_line(&self) -> Vec<Move> {
+        return self.line_moves(0);
+    }
+
+    // Moves of the line after the first `ply`, walking the tree once
+    fn line_moves(&self, ply: usize) -> Vec<Move> {
+        let mut node = self.node(ply);
         let mut moves = Vec::new();
-        for to in 0..9 {
-            for size in 0..3 {
-                if !self.b.is_free(size, to) {
-                    continue;
-                }
-                for from in 0..9 {
-                    if !self.b.is_movable(size, from) {
-                        continue;
-                    }
-                    moves.push(Move {
-                        is_new: false,
-                        size,
-                        from,
-                        to,
-                    });
-                }
-                if !self.b.is_left(size) {
-                    continue;
-                }
-                moves.push(Move {
-                    is_new: true,
-                    size,
-                    from: -1,
-                    to,
-                });
-            }
+        for &idx in &self.line[ply..] {
+            node = &node.children[idx];
+            moves.push(node.m.unwrap());
         }
         return moves;
     }
+
+    pub fn goto_ply(&mut self, ply: usize) -> bool {
+        if ply > self.line_len() {
+            return false;
+        }
+        while self.ply() > ply {
+            self.undo_move();
+        }
+        let ahead = ply - self.ply();
+        for m in self.line_moves(self.ply()).into_iter().take(ahead) {
+            self.replay(m);
+        }
+        return true;
+    }
+
+    pub fn goto_first(&mut self) {
+        self.goto_ply(0);
+    }
+
+    pub fn goto_last(&mut self) {
+        self.goto_ply(self.line_len());
+    }
+
+    pub fn is_main_line(&self) -> bool {
+        return self.line.iter().all(|&idx| idx == 0);
+    }
+
+    pub fn get_main_line(&self) -> Vec<Move> {
+        let mut line = Vec::new();
+        let mut node = &self.tree;
+        while let Some(next) = node.children.first() {
+            line.push(next.m.unwrap());
+            node = next;
+        }
+        return line;
+    }
+
+    // Leaves the variation at the move where it branched off
+    pub fn goto_main_line(&mut self) {
+        let shared = self.line.iter().take_while(|&&idx| idx == 0).count();
+        self.goto_ply(shared.min(self.ply()));
+        self.line.truncate(shared);
+        self.extend_line();
+    }
+
+    // Makes the current line the main line
+    pub fn promote_variation(&mut self) {
+        for ply in 0..self.line.len() {
+            let idx = self.line[ply];
+            let children = &mut self.node_mut(ply).children;
+            let child = children.remove(idx);
+            children.insert(0, child);
+            self.line[ply] = 0;
+        }
+    }
+
+    // Drops the current move and everything after it, the cursor steps back
+    pub fn delete_variation(&mut self) -> bool {
+        let ply = self.ply();
+        if ply == 0 {
+            return false;
+        }
+        self.undo_move();
+        let idx = self.line[ply - 1];
+        self.node_mut(ply - 1).children.remove(idx);
+        self.line.truncate(ply - 1);
+        self.extend_line();
+        return true;
+    }
+
+    // Moves that continue the current position in the tree, main line first
+    pub fn get_variations(&self) -> Vec<Move> {
+        return self
+            .node(self.ply())
+            .children
+            .iter()
+            .map(|c| c.m.unwrap())
+            .collect();
+    }
+
+    pub fn get_tree(&self) -> &Node {
+        return &self.tree;
+    }
+
+    // Comment on the last move, or on the game at the start
+    pub fn get_comment(&self) -> &str {
+        return &self.node(self.ply()).comment;
+    }
+
+    pub fn set_comment(&mut self, comment: &str) {
+        self.node_mut(self.ply()).comment = comment.to_string();
+    }
+
+    pub fn get_nags(&self) -> &[u8] {
+        return &self.node(self.ply()).nags;
+    }
+
+    pub fn add_nag(&mut self, nag: u8) {
+        let nags = &mut self.node_mut(self.ply()).nags;
+        if !nags.contains(&nag) {
+            nags.push(nag);
+        }
+    }
+
+    pub fn remove_nag(&mut self, nag: u8) {
+        self.node_mut(self.ply()).nags.retain(|&n| n != nag);
+    }
+
+    // The clock starts paused, it runs for whoever is to move
+    pub fn set_clock(&mut self, control: TimeControl, source: Arc<dyn ClockSource>) {
+        let mut clock = Clock::new(control, source);
+        clock.switch(self.b.player);
+        self.clock = Some(clock);
+        // A timeout of the clock replaced no longer ends the game
+        self.update_state();
+    }
+
+    pub fn remove_clock(&mut self) {
+        self.clock = None;
+        self.update_state();
+    }
+
+    pub fn get_clock(&self) -> Option<&Clock> {
+        return self.clock.as_ref();
+    }
+
+    pub fn set_clock_source(&mut self, source: Arc<dyn ClockSource>) {
+        if let Some(clock) = &mut self.clock {
+            clock.set_source(source);
+        }
+    }
+
+    pub fn start_clock(&mut self) {
+        if self.s != State::InGame {
+            return;
+        }
+        if let Some(clock) = &mut self.clock {
+            clock.start();
+        }
+    }
+
+    pub fn pause_clock(&mut self) {
+        if let Some(clock) = &mut self.clock {
+            clock.pause();
+        }
+    }
+
+    // True once the player to move ran out of time, the game is then lost for them
+    pub fn check_clock(&mut self) -> bool {
+        let Some(clock) = &mut self.clock else {
+            return false;
+        };
+        if clock.check_flag().is_none() {
+            return false;
+        }
+        self.update_state();
+        return true;
+    }
+
+    pub fn get_moves(&self) -> Vec<Move> {
+        return self.b.get_moves();
+    }
 }
@@ -1,7 +1,25 @@
-use gobblers::search::Search;
+use gobblers::search::{Report, Search};
 
 fn main() {
     let mut s = Search::new();
-    s.pre_evaluate(4, 10);
-    s.flush();
+    s.set_progress(|r| match r {
+        Report::Queued { count, queued } => println!("Count {}, added {}", count, queued),
+        Report::Evaluated {
+            index,
+            total,
+            id,
+            eval,
+        } => println!("done {}/{} {}: {:?}", index + 1, total, id, eval),
+        Report::Checkpoint {
+            done,
+            total,
+            elapsed,
+            eta,
+        } => println!(
+            "checkpoint {}/{} after {:.0}s, eta {:.0}s",
+            done, total, elapsed, eta
+        ),
+        Report::Iteration(_) => (),
+    });
+    s.resume_pre_evaluate("pre_evaluate.job", 4, 10).unwrap();
 }
@@ -0,0 +1,200 @@
+use std::cmp::Reverse;
+
+use crate::{rng::Rng, search::deepening, search::EvalKind, Board, GameBoard, Move};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MctsConfig {
+    pub playouts: u32,
+    pub exploration: f32,
+    // Depth of the exact search run on new nodes, 0 disables it
+    pub solver_depth: i32,
+    // Playouts longer than this count as a draw
+    pub max_plies: u32,
+    pub seed: u64,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        return MctsConfig {
+            playouts: 10000,
+            exploration: 1.4,
+            solver_depth: 0,
+            max_plies: 64,
+            seed: 0,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoveStats {
+    pub m: Move,
+    pub visits: u32,
+    // Average reward for the side to move, 1 win, 0.5 draw, 0 loss
+    pub value: f32,
+}
+
+// Rewards are seen from the player who made the node's move
+struct Node {
+    m: Move,
+    parent: usize,
+    children: Vec<usize>,
+    untried: Vec<Move>,
+    visits: u32,
+    reward: f32,
+    result: Option<f32>,
+}
+
+pub struct Mcts {
+    pub config: MctsConfig,
+    rng: Rng,
+}
+
+impl Mcts {
+    pub fn new(config: MctsConfig) -> Mcts {
+        return Mcts {
+            config,
+            rng: Rng::new(config.seed),
+        };
+    }
+
+    pub fn best_move(&mut self, b: &GameBoard) -> Option<Move> {
+        return self.analyse(b).first().map(|s| s.m);
+    }
+
+    // Root moves sorted by visits, most visited first
+    pub fn analyse(&mut self, b: &GameBoard) -> Vec<MoveStats> {
+        let root = *b.get_board();
+        if root.get_state() != 0 {
+            return Vec::new();
+        }
+        let mut nodes = vec![Node {
+            m: Move {
+                is_new: false,
+                size: 0,
+                from: 0,
+                to: 0,
+            },
+            parent: usize::MAX,
+            children: Vec::new(),
+            untried: root.get_moves(),
+            visits: 0,
+            reward: 0.0,
+            result: None,
+        }];
+        for _ in 0..self.config.playouts {
+            let mut board = root;
+            let mut idx = 0;
+            // Selection
+            while nodes[idx].result.is_none()
+                && nodes[idx].untried.is_empty()
+                && !nodes[idx].children.is_empty()
+            {
+                idx = self.select(&nodes, idx);
+                board.do_move(nodes[idx].m);
+            }
+            // Expansion
+            if nodes[idx].result.is_none() && !nodes[idx].untried.is_empty() {
+                let i = self.rng.below(nodes[idx].untried.len());
+                let m = nodes[idx].untried.swap_remove(i);
+                board.do_move(m);
+                let result = self.terminal(&board);
+                let untried = match result {
+                    Some(_) => Vec::new(),
+                    None => board.get_moves(),
+                };
+                nodes.push(Node {
+                    m,
+                    parent: idx,
+                    children: Vec::new(),
+                    untried,
+                    visits: 0,
+                    reward: 0.0,
+                    result,
+                });
+                let child = nodes.len() - 1;
+                nodes[idx].children.push(child);
+                idx = child;
+            }
+            // Simulation
+            let mut reward = match nodes[idx].result {
+                Some(r) => r,
+                None if idx == 0 || nodes[idx].untried.is_empty() => 0.5,
+                None => self.playout(board),
+            };
+            // Backpropagation
+            while idx != usize::MAX {
+                nodes[idx].visits += 1;
+                nodes[idx].reward += reward;
+                reward = 1.0 - reward;
+                idx = nodes[idx].parent;
+            }
+        }
+        let mut stats: Vec<MoveStats> = nodes[0]
+            .children
+            .iter()
+            .map(|&c| MoveStats {
+                m: nodes[c].m,
+                visits: nodes[c].visits,
+                value: nodes[c].reward / nodes[c].visits as f32,
+            })
+            .collect();
+        stats.sort_by_key(|s| Reverse(s.visits));
+        return stats;
+    }
+
+    fn select(&self, nodes: &[Node], idx: usize) -> usize {
+        let ln = (nodes[idx].visits as f32).ln();
+        let uct = |c: usize| {
+            let n = nodes[c].visits as f32;
+            return nodes[c].reward / n + self.config.exploration * (ln / n).sqrt();
+        };
+        let mut best = nodes[idx].children[0];
+        for &c in &nodes[idx].children[1..] {
+            if uct(c) > uct(best) {
+                best = c;
+            }
+        }
+        return best;
+    }
+
+    // Fixed reward for the player who just moved, if the game is decided
+    fn terminal(&self, b: &Board) -> Option<f32> {
+        match b.get_state() {
+            1 => return Some(0.0),
+            2 => return Some(1.0),
+            3 => return Some(0.5),
+            _ => (),
+        }
+        if self.config.solver_depth <= 0 {
+            return None;
+        }
+        return match deepening(b, self.config.solver_depth, None, None).kind {
+            EvalKind::Win => Some(0.0),
+            EvalKind::Loss => Some(1.0),
+            EvalKind::Draw => Some(0.5),
+            EvalKind::TooFar => None,
+        };
+    }
+
+    // Random game from `b`, reward for the player who just moved
+    fn playout(&mut self, mut b: Board) -> f32 {
+        let mover = b.player ^ 1;
+        for _ in 0..self.config.max_plies {
+            let winner = match b.get_state() {
+                1 => b.player,
+                2 => b.player ^ 1,
+                3 => return 0.5,
+                _ => {
+                    let moves = b.get_moves();
+                    if moves.is_empty() {
+                        return 0.5;
+                    }
+                    b.do_move(moves[self.rng.below(moves.len())]);
+                    continue;
+                }
+            };
+            return if winner == mover { 1.0 } else { 0.0 };
+        }
+        return 0.5;
+    }
+}
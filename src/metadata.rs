@@ -0,0 +1,119 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{actions::Ending, bot::Difficulty, GameBoard, State};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EngineSettings {
+    pub difficulty: Option<Difficulty>,
+    pub max_depth: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct PlayerInfo {
+    pub name: String,
+    pub id: Option<String>,
+    // Set when an engine played this side
+    pub engine: Option<EngineSettings>,
+}
+
+// Results are written from the first player's side, like 1-0 in chess
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GameResult {
+    #[default]
+    Ongoing,
+    FirstWins,
+    SecondWins,
+    Draw,
+}
+
+impl Display for GameResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            GameResult::Ongoing => "*",
+            GameResult::FirstWins => "1-0",
+            GameResult::SecondWins => "0-1",
+            GameResult::Draw => "½-½",
+        };
+        f.write_str(s)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Termination {
+    Line,
+    Timeout,
+    Resignation,
+    Agreement,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Metadata {
+    pub players: [PlayerInfo; 2],
+    pub variant: String,
+    // Seconds since the unix epoch
+    pub start_time: Option<u64>,
+    pub result: GameResult,
+    pub termination: Option<Termination>,
+}
+
+impl Default for Metadata {
+    fn default() -> Self {
+        return Metadata {
+            players: Default::default(),
+            variant: "standard".to_string(),
+            start_time: None,
+            result: GameResult::Ongoing,
+            termination: None,
+        };
+    }
+}
+
+impl GameBoard {
+    pub fn get_metadata(&self) -> &Metadata {
+        return &self.metadata;
+    }
+
+    // Result and termination are kept up to date by the game
+    pub fn set_metadata(&mut self, metadata: Metadata) {
+        self.metadata = metadata;
+        self.track_result();
+    }
+
+    pub fn get_result(&self) -> GameResult {
+        return self.metadata.result;
+    }
+
+    // The game's result is how it was ended, or where the main line ends.
+    // While the main line is unfinished a variation played to the end of
+    // the game gives the result, after that variations are only analysis.
+    pub(crate) fn track_result(&mut self) {
+        let ending = self.get_ending();
+        let at_end = self.ply() == self.line_len();
+        let decides = match self.is_main_line() {
+            true => at_end,
+            false => {
+                at_end && self.s != State::InGame && self.metadata.result == GameResult::Ongoing
+            }
+        };
+        if ending.is_none() && !decides {
+            return;
+        }
+        let first = self.player() == 0;
+        self.metadata.result = match self.s {
+            State::InGame => GameResult::Ongoing,
+            State::Draw => GameResult::Draw,
+            State::Win if first => GameResult::FirstWins,
+            State::Loss if !first => GameResult::FirstWins,
+            _ => GameResult::SecondWins,
+        };
+        self.metadata.termination = match (self.s, ending) {
+            (State::InGame, _) => None,
+            (_, Some(Ending::Timeout(_))) => Some(Termination::Timeout),
+            (_, Some(Ending::Resign(_))) => Some(Termination::Resignation),
+            (_, Some(Ending::Agreement)) => Some(Termination::Agreement),
+            (_, None) => Some(Termination::Line),
+        };
+    }
+}
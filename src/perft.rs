@@ -0,0 +1,39 @@
+use crate::{Board, Move};
+
+// Leaf count of the move tree `depth` plies deep. Finished games have no
+// moves, as in `GameBoard`, so they only count when `depth` is reached.
+pub fn perft(b: &Board, depth: i32) -> u64 {
+    let mut b = *b;
+    return count(&mut b, depth);
+}
+
+fn count(b: &mut Board, depth: i32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    if b.get_state() != 0 {
+        return 0;
+    }
+    let mut nodes = 0;
+    for m in b.get_moves() {
+        b.do_move(m);
+        nodes += count(b, depth - 1);
+        b.undo_move(m);
+    }
+    return nodes;
+}
+
+// Perft split by the first move, to find where two generators diverge
+pub fn divide(b: &Board, depth: i32) -> Vec<(Move, u64)> {
+    let mut b = *b;
+    if depth == 0 || b.get_state() != 0 {
+        return Vec::new();
+    }
+    let mut split = Vec::new();
+    for m in b.get_moves() {
+        b.do_move(m);
+        split.push((m, count(&mut b, depth - 1)));
+        b.undo_move(m);
+    }
+    return split;
+}
@@ -0,0 +1,62 @@
+use std::collections::HashSet;
+
+use crate::Board;
+
+// Breadth-first iterator over the positions reachable within `max_plies`,
+// each yielded once per symmetry class together with the first ply it was
+// reached at. Only ids are kept: the seen set and the current and next ply.
+pub struct Positions {
+    seen: HashSet<u64>,
+    current: Vec<u64>,
+    next: Vec<u64>,
+    idx: usize,
+    ply: i32,
+    max_plies: i32,
+}
+
+impl Positions {
+    pub fn new(start: &Board, max_plies: i32) -> Positions {
+        let id = start.get_max_id();
+        return Positions {
+            seen: HashSet::from([id]),
+            current: vec![id],
+            next: Vec::new(),
+            idx: 0,
+            ply: 0,
+            max_plies,
+        };
+    }
+
+    // Unique positions found so far, including queued ones
+    pub fn seen(&self) -> usize {
+        return self.seen.len();
+    }
+}
+
+impl Iterator for Positions {
+    type Item = (i32, Board);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.idx == self.current.len() {
+            if self.next.is_empty() {
+                return None;
+            }
+            self.current = std::mem::take(&mut self.next);
+            self.idx = 0;
+            self.ply += 1;
+        }
+        let b = Board::from_id(self.current[self.idx]);
+        self.idx += 1;
+        if self.ply < self.max_plies && b.get_state() == 0 {
+            for m in b.get_moves() {
+                let mut child = b;
+                child.do_move(m);
+                let id = child.get_max_id();
+                if self.seen.insert(id) {
+                    self.next.push(id);
+                }
+            }
+        }
+        return Some((self.ply, b));
+    }
+}
@@ -0,0 +1,278 @@
+use std::{fmt::Debug, fs, io, path::Path, sync::Arc};
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    actions::Event,
+    clock::{Clock, ClockState, SystemClock},
+    metadata::Metadata,
+    Board, GameBoard, Move,
+};
+
+// Annotation glyphs, numbered like in PGN
+pub const NAG_GOOD: u8 = 1;
+pub const NAG_MISTAKE: u8 = 2;
+pub const NAG_BRILLIANT: u8 = 3;
+pub const NAG_BLUNDER: u8 = 4;
+pub const NAG_INTERESTING: u8 = 5;
+pub const NAG_DUBIOUS: u8 = 6;
+
+// One position of the game tree, reached by `m`. The first child is the
+// main continuation, the others are variations. A line is as deep as the
+// game is long, so everything walking the tree does so without recursion.
+pub struct Node {
+    pub m: Option<Move>,
+    pub comment: String,
+    pub nags: Vec<u8>,
+    pub children: Vec<Node>,
+}
+
+// A node without its children, how many it has stands in for them. A tree
+// is saved as its nodes in preorder.
+#[derive(Serialize)]
+struct FlatRef<'a> {
+    m: Option<Move>,
+    comment: &'a str,
+    nags: &'a [u8],
+    children: usize,
+}
+
+#[derive(Deserialize)]
+struct Flat {
+    m: Option<Move>,
+    comment: String,
+    nags: Vec<u8>,
+    children: usize,
+}
+
+impl Node {
+    pub fn root() -> Node {
+        return Node {
+            m: None,
+            comment: String::new(),
+            nags: Vec::new(),
+            children: Vec::new(),
+        };
+    }
+
+    pub fn new(m: Move) -> Node {
+        let mut node = Node::root();
+        node.m = Some(m);
+        return node;
+    }
+
+    // This node and all below it, parents before their children
+    pub fn preorder(&self) -> impl Iterator<Item = &Node> {
+        let mut stack = vec![self];
+        return std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(node.children.iter().rev());
+            return Some(node);
+        });
+    }
+
+    fn flatten(&self) -> Vec<FlatRef<'_>> {
+        return self
+            .preorder()
+            .map(|n| FlatRef {
+                m: n.m,
+                comment: &n.comment,
+                nags: &n.nags,
+                children: n.children.len(),
+            })
+            .collect();
+    }
+
+    // None unless `flat` is exactly one tree
+    fn unflatten(flat: impl IntoIterator<Item = Flat>) -> Option<Node> {
+        let mut flat = flat.into_iter();
+        // Nodes still missing children, with how many they miss
+        let mut stack: Vec<(Node, usize)> = Vec::new();
+        loop {
+            let f = flat.next()?;
+            let node = Node {
+                m: f.m,
+                comment: f.comment,
+                nags: f.nags,
+                children: Vec::new(),
+            };
+            stack.push((node, f.children));
+            while stack.last()?.1 == 0 {
+                let (done, _) = stack.pop().unwrap();
+                match stack.last_mut() {
+                    Some((parent, missing)) => {
+                        parent.children.push(done);
+                        *missing -= 1;
+                    }
+                    None => return flat.next().is_none().then_some(done),
+                }
+            }
+        }
+    }
+
+    // Every move is legal where it is played and only the root has none
+    fn is_valid(&self, b: &Board) -> bool {
+        let mut stack = vec![(self, *b)];
+        while let Some((node, b)) = stack.pop() {
+            if b.get_state() != 0 && !node.children.is_empty() {
+                return false;
+            }
+            let moves = b.get_moves();
+            for child in &node.children {
+                let Some(m) = child.m.filter(|m| moves.contains(m)) else {
+                    return false;
+                };
+                let mut next = b;
+                next.do_move(m);
+                stack.push((child, next));
+            }
+        }
+        return true;
+    }
+}
+
+impl Clone for Node {
+    fn clone(&self) -> Self {
+        let flat = self.preorder().map(|n| Flat {
+            m: n.m,
+            comment: n.comment.clone(),
+            nags: n.nags.clone(),
+            children: n.children.len(),
+        });
+        return Node::unflatten(flat).unwrap();
+    }
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        fn key(n: &Node) -> (Option<Move>, &str, &[u8], usize) {
+            return (n.m, &n.comment, &n.nags, n.children.len());
+        }
+        return self.preorder().map(key).eq(other.preorder().map(key));
+    }
+}
+
+impl Eq for Node {}
+
+impl Drop for Node {
+    fn drop(&mut self) {
+        let mut stack = std::mem::take(&mut self.children);
+        while let Some(mut node) = stack.pop() {
+            stack.append(&mut node.children);
+        }
+    }
+}
+
+impl Debug for Node {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let flat = self.flatten();
+        let entries = flat.iter().map(|n| (n.m, n.comment, n.nags, n.children));
+        return f.debug_list().entries(entries).finish();
+    }
+}
+
+impl Serialize for Node {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return self.flatten().serialize(serializer);
+    }
+}
+
+impl<'de> Deserialize<'de> for Node {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let flat = Vec::<Flat>::deserialize(deserializer)?;
+        return Node::unflatten(flat).ok_or_else(|| D::Error::custom("malformed game tree"));
+    }
+}
+
+// Saved game: the whole tree and where the cursor was
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Record {
+    pub start: Board,
+    pub tree: Node,
+    pub line: Vec<usize>,
+    pub ply: usize,
+    pub clock: Option<ClockState>,
+    pub events: Vec<Event>,
+    pub draw_offer: Option<i32>,
+    pub takeback_request: Option<i32>,
+    pub metadata: Metadata,
+}
+
+impl GameBoard {
+    pub fn to_record(&self) -> Record {
+        return Record {
+            start: self.start,
+            tree: self.tree.clone(),
+            line: self.line.clone(),
+            ply: self.ply(),
+            clock: self.clock.as_ref().map(|c| c.state()),
+            events: self.events.clone(),
+            draw_offer: self.draw_offer,
+            takeback_request: self.takeback_request,
+            metadata: self.metadata.clone(),
+        };
+    }
+
+    // None if the record does not describe a legal game. A saved clock is
+    // restored paused on the system clock.
+    pub fn from_record(record: Record, auto_select: bool) -> Option<GameBoard> {
+        let mut g = GameBoard::from_position(record.start, auto_select).ok()?;
+        if record.tree.m.is_some() || !record.tree.is_valid(&record.start) {
+            return None;
+        }
+        let mut node = &record.tree;
+        for &idx in &record.line {
+            node = node.children.get(idx)?;
+        }
+        if record.ply > record.line.len() {
+            return None;
+        }
+        let is_player = |p: &i32| (0..2).contains(p);
+        if !record.events.iter().all(|e| is_player(&e.player))
+            || !record
+                .draw_offer
+                .iter()
+                .chain(&record.takeback_request)
+                .all(is_player)
+        {
+            return None;
+        }
+        g.tree = record.tree;
+        g.line = record.line;
+        g.extend_line();
+        g.goto_ply(record.ply);
+        g.events = record.events;
+        g.draw_offer = record.draw_offer;
+        g.takeback_request = record.takeback_request;
+        g.metadata = record.metadata;
+        g.update_state();
+        if let Some(state) = record.clock {
+            if state.flagged.is_some_and(|p| !(0..2).contains(&p)) {
+                return None;
+            }
+            g.clock = Some(Clock::from_state(
+                state,
+                g.b.player,
+                Arc::new(SystemClock::new()),
+            ));
+            g.update_state();
+        }
+        return Some(g);
+    }
+
+    pub fn load(path: impl AsRef<Path>, auto_select: bool) -> io::Result<GameBoard> {
+        let bytes = fs::read(path)?;
+        let record = bincode::deserialize(&bytes)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        return GameBoard::from_record(record, auto_select)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "illegal game record"));
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let bytes = bincode::serialize(&self.to_record()).unwrap();
+        let path = path.as_ref();
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, bytes)?;
+        return fs::rename(tmp, path);
+    }
+}
@@ -0,0 +1,338 @@
+use std::fmt::Display;
+
+use crate::{actions::Ending, rng::Rng, Board, GameBoard, Move, Select, State};
+
+const LINES: [[usize; 3]; 8] = [
+    [0, 1, 2],
+    [3, 4, 5],
+    [6, 7, 8],
+    [0, 3, 6],
+    [1, 4, 7],
+    [2, 5, 8],
+    [0, 4, 8],
+    [2, 4, 6],
+];
+
+// Slow model of the rules to check `Board` against: every cell holds its
+// stack of (player, size) pieces, biggest on top.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefBoard {
+    pub cells: [Vec<(i32, i32)>; 9],
+    pub left: [[i32; 3]; 2],
+    pub player: i32,
+}
+
+impl RefBoard {
+    pub fn new() -> RefBoard {
+        return RefBoard {
+            cells: Default::default(),
+            left: [[2; 3]; 2],
+            player: 0,
+        };
+    }
+
+    pub fn top(&self, pos: usize) -> Option<(i32, i32)> {
+        return self.cells[pos].last().copied();
+    }
+
+    pub fn moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        for size in 0..3 {
+            for to in 0..9 {
+                if self.top(to).is_some_and(|(_, s)| s >= size) {
+                    continue;
+                }
+                if self.left[self.player as usize][size as usize] > 0 {
+                    moves.push(Move {
+                        is_new: true,
+                        size,
+                        from: -1,
+                        to: to as i32,
+                    });
+                }
+                for from in 0..9 {
+                    if self.top(from) == Some((self.player, size)) {
+                        moves.push(Move {
+                            is_new: false,
+                            size,
+                            from: from as i32,
+                            to: to as i32,
+                        });
+                    }
+                }
+            }
+        }
+        return moves;
+    }
+
+    // `m` has to be one of `moves`
+    pub fn do_move(&mut self, m: Move) {
+        match m.is_new {
+            true => self.left[self.player as usize][m.size as usize] -= 1,
+            false => {
+                self.cells[m.from as usize].pop();
+            }
+        }
+        self.cells[m.to as usize].push((self.player, m.size));
+        self.player ^= 1;
+    }
+
+    pub fn undo_move(&mut self, m: Move) {
+        self.player ^= 1;
+        self.cells[m.to as usize].pop();
+        match m.is_new {
+            true => self.left[self.player as usize][m.size as usize] += 1,
+            false => self.cells[m.from as usize].push((self.player, m.size)),
+        }
+    }
+
+    pub fn view(&self, player: i32) -> i32 {
+        let mut view = 0;
+        for pos in 0..9 {
+            if self.top(pos).is_some_and(|(p, _)| p == player) {
+                view |= 1 << pos;
+            }
+        }
+        return view;
+    }
+
+    pub fn has_line(&self, player: i32) -> bool {
+        let view = self.view(player);
+        return LINES
+            .iter()
+            .any(|line| line.iter().all(|pos| view & (1 << pos) != 0));
+    }
+
+    // Same encoding as `Board::get_state`
+    pub fn state(&self) -> i32 {
+        let win = self.has_line(self.player) as i32;
+        let loss = self.has_line(self.player ^ 1) as i32;
+        return win | (loss << 1);
+    }
+
+    // The two other cells of a line through `pos` show the player to move
+    pub fn is_winning_spot(&self, pos: usize) -> bool {
+        let view = self.view(self.player);
+        return LINES.iter().any(|line| {
+            line.contains(&pos)
+                && line
+                    .iter()
+                    .all(|&other| other == pos || view & (1 << other) != 0)
+        });
+    }
+
+    pub fn from_board(b: &Board) -> RefBoard {
+        let mut r = RefBoard::new();
+        for (pos, cell) in r.cells.iter_mut().enumerate() {
+            for size in 0..3 {
+                if let Some(p) = (0..2).find(|p| b.layers[p * 3 + size] & (1 << pos) != 0) {
+                    cell.push((p as i32, size as i32));
+                }
+            }
+        }
+        for p in 0..2 {
+            for size in 0..3 {
+                r.left[p][size] = b.pieces[p * 3 + size];
+            }
+        }
+        r.player = b.player;
+        return r;
+    }
+
+    pub fn to_board(&self) -> Board {
+        let mut b = Board::new();
+        for (pos, cell) in self.cells.iter().enumerate() {
+            for &(p, size) in cell {
+                b.layers[(p * 3 + size) as usize] |= 1 << pos;
+            }
+        }
+        for p in 0..2 {
+            for size in 0..3 {
+                b.pieces[p * 3 + size] = self.left[p][size];
+            }
+        }
+        b.player = self.player;
+        return b;
+    }
+}
+
+impl Default for RefBoard {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+// First difference found between `Board` and `RefBoard`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    pub history: Vec<Move>,
+    pub board: Board,
+    pub reference: RefBoard,
+    pub what: String,
+}
+
+impl Display for Divergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} after {:?}\n{:?}",
+            self.what, self.history, self.board
+        )
+    }
+}
+
+fn sorted(mut moves: Vec<Move>) -> Vec<Move> {
+    moves.sort_by_key(|m| (m.is_new, m.size, m.from, m.to));
+    return moves;
+}
+
+// Describes the first mismatch between the two models, if any
+pub fn compare(b: &Board, r: &RefBoard) -> Option<String> {
+    if *b != r.to_board() {
+        return Some(format!("state, expected {:?}", r.to_board()));
+    }
+    for p in 0..2 {
+        if b.get_view(p) != r.view(p) {
+            let (got, want) = (b.get_view(p), r.view(p));
+            return Some(format!("view {}: {:09b} != {:09b}", p, got, want));
+        }
+    }
+    if b.get_state() != r.state() {
+        return Some(format!("get_state: {} != {}", b.get_state(), r.state()));
+    }
+    for pos in 0..9 {
+        if b.is_winning_spot(pos as i32) != r.is_winning_spot(pos) {
+            return Some(format!("is_winning_spot({})", pos));
+        }
+    }
+    let (got, want) = (sorted(b.get_moves()), sorted(r.moves()));
+    if got != want {
+        return Some(format!("moves: {:?} != {:?}", got, want));
+    }
+    return None;
+}
+
+// Plays `games` random games of up to `max_plies` on both models, undoing a
+// move now and then, and stops at the first divergence
+pub fn differential(seed: u64, games: usize, max_plies: usize) -> Result<(), Box<Divergence>> {
+    let mut rng = Rng::new(seed);
+    for _ in 0..games {
+        let mut b = Board::new();
+        let mut r = RefBoard::new();
+        let mut history: Vec<Move> = Vec::new();
+        for _ in 0..max_plies {
+            if let Some(what) = compare(&b, &r) {
+                return Err(Box::new(Divergence {
+                    history,
+                    board: b,
+                    reference: r,
+                    what,
+                }));
+            }
+            if !history.is_empty() && rng.chance(0.1) {
+                let m = history.pop().unwrap();
+                b.undo_move(m);
+                r.undo_move(m);
+                continue;
+            }
+            let moves = r.moves();
+            if r.state() != 0 || moves.is_empty() {
+                break;
+            }
+            let m = moves[rng.below(moves.len())];
+            b.do_move(m);
+            r.do_move(m);
+            history.push(m);
+        }
+    }
+    return Ok(());
+}
+
+// Replays the history of `g` on a `RefBoard` and describes the first
+// thing `g` reports differently, if any
+pub fn check_game_board(g: &GameBoard) -> Option<String> {
+    let mut r = RefBoard::from_board(g.get_start());
+    for &m in g.get_history() {
+        if r.state() != 0 || !r.moves().contains(&m) {
+            return Some(format!("illegal move {:?} in history", m));
+        }
+        r.do_move(m);
+    }
+    if let Some(what) = compare(g.get_board(), &r) {
+        return Some(what);
+    }
+    let state = match r.state() {
+        0 => State::InGame,
+        1 => State::Win,
+        2 => State::Loss,
+        _ => State::Draw,
+    };
+    // Resigning, agreeing to a draw or running out of time ends the game
+    // whatever the board shows
+    let state = match g.get_ending() {
+        None => state,
+        Some(Ending::Agreement) => State::Draw,
+        Some(Ending::Timeout(p) | Ending::Resign(p)) if p == r.player => State::Loss,
+        Some(_) => State::Win,
+    };
+    if g.get_state() != state {
+        return Some(format!("state {:?} != {:?}", g.get_state(), state));
+    }
+    for pos in 0..9 {
+        if g.get_top(pos) != r.top(pos as usize) {
+            return Some(format!("get_top({})", pos));
+        }
+    }
+    for p in 0..2 {
+        for size in 0..3 {
+            if g.get_left(p, size) != r.left[p as usize][size as usize] {
+                return Some(format!("get_left({}, {})", p, size));
+            }
+        }
+    }
+    let moves = match state {
+        State::InGame => r.moves(),
+        _ => Vec::new(),
+    };
+    let targets = |is_new: bool, size: i32, from: i32| {
+        return moves
+            .iter()
+            .filter(|x| x.is_new == is_new && x.size == size && (is_new || x.from == from))
+            .fold(0, |mask, x| mask | (1 << x.to));
+    };
+    for pos in 0..9 {
+        let size = r.top(pos as usize).map_or(0, |(_, s)| s);
+        if g.legal_targets(pos) != targets(false, size, pos) {
+            return Some(format!("legal_targets({})", pos));
+        }
+    }
+    for size in 0..3 {
+        if g.legal_targets_new(size) != targets(true, size, -1) {
+            return Some(format!("legal_targets_new({})", size));
+        }
+    }
+    let sources = moves
+        .iter()
+        .fold((0, 0), |(cells, sizes), m| match m.is_new {
+            true => (cells, sizes | (1 << m.size)),
+            false => (cells | (1 << m.from), sizes),
+        });
+    if g.movable_sources() != sources {
+        return Some(format!(
+            "movable_sources {:?} != {:?}",
+            g.movable_sources(),
+            sources
+        ));
+    }
+    let (sel, m) = g.get_select();
+    if sel != Select::None && state != State::InGame {
+        return Some(format!("selection {:?} in finished game", sel));
+    }
+    if sel == Select::Move && !r.moves().contains(&m) {
+        return Some(format!("selected illegal move {:?}", m));
+    }
+    if sel != Select::None && g.get_select_targets() != targets(m.is_new, m.size, m.from) {
+        return Some(format!("targets {:09b}", g.get_select_targets()));
+    }
+    return None;
+}
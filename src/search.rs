@@ -1,13 +1,28 @@
+pub mod book;
+pub mod cache;
+pub mod job;
+mod pns;
+mod smp;
+pub mod table;
+
 use std::{
-    collections::{HashMap, HashSet},
-    fs,
-    sync::{atomic::AtomicUsize, Arc, Condvar, Mutex},
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
     time::Instant,
 };
 
-use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+use crate::{Board, GameBoard, Move};
 
-use crate::{Board, GameBoard, State};
+use book::Book;
+use cache::{priority, Cache, CacheStats};
+use job::Job;
+use table::{Entry, Table, BOUND_EXACT, BOUND_LOWER, BOUND_UPPER};
 
 const MAX_SCORE: i32 = 10000;
 const MIN_SCORE: i32 = -MAX_SCORE;
@@ -16,15 +31,107 @@ const LOSS_SCORE: i32 = -WIN_SCORE;
 const FAR_SCORE: i32 = 1;
 const DRAW_SCORE: i32 = 0;
 
-fn negamax(b: &mut Board, mut alpha: i32, mut beta: i32, depth: i32, nodes: &mut u64) -> i32 {
-    *nodes += 1;
+const SCOREBOOK: &str = "scorebook";
+// Starts scorebooks keyed by `Board::get_max_id`, followed by the bincode
+// encoded map
+const SCOREBOOK_MAGIC: &[u8; 8] = b"GOBSCOR1";
+
+// 2^20 slots, 16 MiB shared by the parallel search threads
+const TABLE_BITS: u32 = 20;
+// 2^16 slots, 1 MiB for a single search that can't use the shared table
+const PRIVATE_TABLE_BITS: u32 = 16;
+
+struct Ctx<'a> {
+    nodes: u64,
+    tt_probes: u64,
+    tt_hits: u64,
+    cutoffs: u64,
+    root_depth: i32,
+    best: Option<Move>,
+    tt: Option<&'a Table>,
+    stop: Option<&'a AtomicBool>,
+    // Set from outside the search, e.g. to end pondering
+    abort: Option<&'a AtomicBool>,
+}
+
+impl<'a> Ctx<'a> {
+    fn new(tt: Option<&'a Table>, stop: Option<&'a AtomicBool>) -> Ctx<'a> {
+        return Ctx {
+            nodes: 0,
+            tt_probes: 0,
+            tt_hits: 0,
+            cutoffs: 0,
+            root_depth: -1,
+            best: None,
+            tt,
+            stop,
+            abort: None,
+        };
+    }
+
+    fn set_best(&mut self, depth: i32, m: Move) {
+        if depth == self.root_depth {
+            self.best = Some(m);
+        }
+    }
+
+    fn iteration(&self, depth: i32, score: i32, start: &Instant) -> Iteration {
+        return Iteration {
+            depth,
+            kind: to_eval(score, depth, 0.0, 0).kind,
+            nodes: self.nodes,
+            time: start.elapsed().as_secs_f32(),
+            tt_probes: self.tt_probes,
+            tt_hits: self.tt_hits,
+            cutoffs: self.cutoffs,
+            best: self.best,
+        };
+    }
+
+    fn stopped(&self) -> bool {
+        let is_set = |flag: Option<&AtomicBool>| match flag {
+            Some(flag) => flag.load(Ordering::Relaxed),
+            None => false,
+        };
+        return is_set(self.stop) || is_set(self.abort);
+    }
+}
+
+fn is_decisive(score: i32) -> bool {
+    return score.abs() >= WIN_SCORE && score.abs() < MAX_SCORE;
+}
+
+// Win/loss scores carry the remaining depth, the table stores them as
+// plies to the end of the game so they stay valid at other depths.
+fn to_table(score: i32, depth: i32) -> i32 {
+    return if score > 0 {
+        score - depth
+    } else {
+        score + depth
+    };
+}
+
+fn from_table(score: i32, depth: i32) -> Option<i32> {
+    let plies = WIN_SCORE - score.abs();
+    if plies > depth {
+        return None;
+    }
+    return Some(if score > 0 {
+        score + depth
+    } else {
+        score - depth
+    });
+}
+
+fn negamax(b: &mut Board, alpha: i32, mut beta: i32, depth: i32, ctx: &mut Ctx) -> i32 {
+    ctx.nodes += 1;
     match b.get_state() {
         1 => return WIN_SCORE + depth,
         2 => return LOSS_SCORE - depth,
         3 => return DRAW_SCORE,
         _ => (),
     }
-    if depth == 0 {
+    if depth == 0 || ctx.stopped() {
         return FAR_SCORE;
     }
     let best = WIN_SCORE + depth - 1;
@@ -34,6 +141,52 @@ fn negamax(b: &mut Board, mut alpha: i32, mut beta: i32, depth: i32, nodes: &mut
             return alpha;
         }
     }
+    let Some(tt) = ctx.tt else {
+        return negamax_moves(b, alpha, beta, depth, ctx);
+    };
+    // Only decisive scores are shared, FAR_SCORE is not a real bound
+    let key = Table::key(b);
+    ctx.tt_probes += 1;
+    if let Some(e) = tt.probe(key) {
+        if let Some(score) = from_table(e.score, depth) {
+            let hit = match e.bound {
+                BOUND_EXACT => true,
+                BOUND_LOWER => score >= beta,
+                BOUND_UPPER => score <= alpha,
+                _ => false,
+            };
+            if hit {
+                ctx.tt_hits += 1;
+                return score;
+            }
+        }
+    }
+    let score = negamax_moves(b, alpha, beta, depth, ctx);
+    if is_decisive(score) && !ctx.stopped() {
+        let bound = if score >= beta {
+            BOUND_LOWER
+        } else if score <= alpha {
+            BOUND_UPPER
+        } else {
+            BOUND_EXACT
+        };
+        tt.store(
+            key,
+            Entry {
+                score: to_table(score, depth),
+                depth,
+                bound,
+            },
+        );
+    }
+    return score;
+}
+
+// Moves in the order negamax tries them: onto winning spots first, then new
+// pieces covering others, the other new pieces and last the board moves.
+// Moves onto winning spots come up twice. `f` must leave the board as it got
+// it and stops the iteration by returning false.
+fn for_each_move(b: &mut Board, mut f: impl FnMut(&mut Board, Move) -> bool) -> bool {
     // Winning
     for to in 0..9 {
         if !b.is_winning_spot(to) {
@@ -44,36 +197,28 @@ fn negamax(b: &mut Board, mut alpha: i32, mut beta: i32, depth: i32, nodes: &mut
                 continue;
             }
             if b.is_left(size) {
-                b.do_new_move(size, to);
-                let mut score = negamax(b, -beta, -alpha, depth - 1, nodes);
-                b.undo_new_move(size, to);
-
-                if score != FAR_SCORE {
-                    score = -score;
-                }
-                if score >= beta {
-                    return score;
-                }
-                if score > alpha {
-                    alpha = score;
+                let m = Move {
+                    is_new: true,
+                    size,
+                    from: -1,
+                    to,
+                };
+                if !f(b, m) {
+                    return false;
                 }
             }
             for from in 0..9 {
                 if !b.is_movable(size, from) {
                     continue;
                 }
-                b.do_board_move(size, from, to);
-                let mut score = negamax(b, -beta, -alpha, depth - 1, nodes);
-                b.undo_board_move(size, from, to);
-
-                if score != FAR_SCORE {
-                    score = -score;
-                }
-                if score >= beta {
-                    return score;
-                }
-                if score > alpha {
-                    alpha = score;
+                let m = Move {
+                    is_new: false,
+                    size,
+                    from,
+                    to,
+                };
+                if !f(b, m) {
+                    return false;
                 }
             }
         }
@@ -87,18 +232,14 @@ fn negamax(b: &mut Board, mut alpha: i32, mut beta: i32, depth: i32, nodes: &mut
             if !b.is_free(size, to) || !b.is_cover(size, to) {
                 continue;
             }
-            b.do_new_move(size, to);
-            let mut score = negamax(b, -beta, -alpha, depth - 1, nodes);
-            b.undo_new_move(size, to);
-
-            if score != FAR_SCORE {
-                score = -score;
-            }
-            if score >= beta {
-                return score;
-            }
-            if score > alpha {
-                alpha = score;
+            let m = Move {
+                is_new: true,
+                size,
+                from: -1,
+                to,
+            };
+            if !f(b, m) {
+                return false;
             }
         }
     }
@@ -111,22 +252,18 @@ fn negamax(b: &mut Board, mut alpha: i32, mut beta: i32, depth: i32, nodes: &mut
             if !b.is_free(size, to) || b.is_cover(size, to) {
                 continue;
             }
-            b.do_new_move(size, to);
-            let mut score = negamax(b, -beta, -alpha, depth - 1, nodes);
-            b.undo_new_move(size, to);
-
-            if score != FAR_SCORE {
-                score = -score;
-            }
-            if score >= beta {
-                return score;
-            }
-            if score > alpha {
-                alpha = score;
+            let m = Move {
+                is_new: true,
+                size,
+                from: -1,
+                to,
+            };
+            if !f(b, m) {
+                return false;
             }
         }
     }
-    // New cover
+    // Board
     for size in (0..3).rev() {
         for to in 0..9 {
             if !b.is_free(size, to) {
@@ -136,23 +273,55 @@ fn negamax(b: &mut Board, mut alpha: i32, mut beta: i32, depth: i32, nodes: &mut
                 if !b.is_movable(size, from) {
                     continue;
                 }
-                b.do_board_move(size, from, to);
-                let mut score = negamax(b, -beta, -alpha, depth - 1, nodes);
-                b.undo_board_move(size, from, to);
-
-                if score != FAR_SCORE {
-                    score = -score;
-                }
-                if score >= beta {
-                    return score;
-                }
-                if score > alpha {
-                    alpha = score;
+                let m = Move {
+                    is_new: false,
+                    size,
+                    from,
+                    to,
+                };
+                if !f(b, m) {
+                    return false;
                 }
             }
         }
     }
-    return alpha;
+    return true;
+}
+
+// The moves negamax searches from `b` in search order, duplicates included
+pub fn search_moves(b: &Board) -> Vec<Move> {
+    let mut moves = Vec::new();
+    let mut b = *b;
+    for_each_move(&mut b, |_, m| {
+        moves.push(m);
+        return true;
+    });
+    return moves;
+}
+
+fn negamax_moves(b: &mut Board, mut alpha: i32, beta: i32, depth: i32, ctx: &mut Ctx) -> i32 {
+    let mut cutoff = None;
+    for_each_move(b, |b, m| {
+        b.do_move(m);
+        let mut score = negamax(b, -beta, -alpha, depth - 1, ctx);
+        b.undo_move(m);
+
+        if score != FAR_SCORE {
+            score = -score;
+        }
+        if score >= beta {
+            ctx.cutoffs += 1;
+            ctx.set_best(depth, m);
+            cutoff = Some(score);
+            return false;
+        }
+        if score > alpha {
+            alpha = score;
+            ctx.set_best(depth, m);
+        }
+        return true;
+    });
+    return cutoff.unwrap_or(alpha);
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -171,147 +340,488 @@ pub struct Evaluation {
     pub nodes: u64,
 }
 
-fn deepening(b: &GameBoard, max_depth: i32) -> Evaluation {
-    let mut nodes = 0;
+fn to_eval(score: i32, depth: i32, time: f32, nodes: u64) -> Evaluation {
+    let mut eval = Evaluation {
+        kind: EvalKind::TooFar,
+        depth: depth as u16,
+        time,
+        nodes,
+    };
+    if score == FAR_SCORE {
+        eval.kind = EvalKind::TooFar;
+    } else if score == DRAW_SCORE {
+        eval.kind = EvalKind::Draw;
+    } else if score < 0 {
+        eval.kind = EvalKind::Loss;
+    } else if score > 0 {
+        eval.kind = EvalKind::Win;
+    }
+    return eval;
+}
+
+// The result is meaningless if `abort` got set during the search
+pub(crate) fn deepening(
+    b: &Board,
+    max_depth: i32,
+    abort: Option<&AtomicBool>,
+    progress: Option<&Progress>,
+) -> Evaluation {
+    let mut ctx = Ctx::new(None, None);
+    ctx.abort = abort;
     let mut depth = 0;
     let start = Instant::now();
     loop {
-        let mut search_b = b.get_board().clone();
-        let score = negamax(&mut search_b, MIN_SCORE, MAX_SCORE, depth, &mut nodes);
+        let mut search_b = *b;
+        ctx.root_depth = depth;
+        let score = negamax(&mut search_b, MIN_SCORE, MAX_SCORE, depth, &mut ctx);
+        if ctx.stopped() {
+            return to_eval(FAR_SCORE, depth, start.elapsed().as_secs_f32(), ctx.nodes);
+        }
+        if let Some(progress) = progress {
+            progress(&Report::Iteration(ctx.iteration(depth, score, &start)));
+        }
         if score != FAR_SCORE || depth >= max_depth {
             let time = start.elapsed().as_secs_f32();
-            let mut eval = Evaluation {
-                kind: EvalKind::TooFar,
-                depth: depth as u16,
-                time,
-                nodes,
-            };
-            if score == FAR_SCORE {
-                eval.kind = EvalKind::TooFar;
-            } else if score == DRAW_SCORE {
-                eval.kind = EvalKind::Draw;
-            } else if score < 0 {
-                eval.kind = EvalKind::Loss;
-            } else if score > 0 {
-                eval.kind = EvalKind::Win;
-            }
-            return eval;
+            return to_eval(score, depth, time, ctx.nodes);
         }
         depth += 1;
     }
 }
 
-#[derive(Clone)]
-struct Store {
-    cache: HashMap<u64, Evaluation>,
-    eval: HashSet<u64>,
+// Statistics of one finished iterative deepening step, counters are
+// cumulative over the whole search
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Iteration {
+    pub depth: i32,
+    pub kind: EvalKind,
+    pub nodes: u64,
+    pub time: f32,
+    pub tt_probes: u64,
+    pub tt_hits: u64,
+    pub cutoffs: u64,
+    pub best: Option<Move>,
 }
 
+impl Iteration {
+    pub fn nps(&self) -> f64 {
+        if self.time <= 0.0 {
+            return 0.0;
+        }
+        return self.nodes as f64 / self.time as f64;
+    }
+
+    pub fn tt_hit_rate(&self) -> f64 {
+        if self.tt_probes == 0 {
+            return 0.0;
+        }
+        return self.tt_hits as f64 / self.tt_probes as f64;
+    }
+
+    pub fn cutoff_rate(&self) -> f64 {
+        if self.nodes == 0 {
+            return 0.0;
+        }
+        return self.cutoffs as f64 / self.nodes as f64;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Report {
+    Iteration(Iteration),
+    // `pre_evaluate` visited `count` positions and queued `queued` of them
+    Queued {
+        count: u64,
+        queued: usize,
+    },
+    // `pre_evaluate` finished position `index` of `total`
+    Evaluated {
+        index: usize,
+        total: usize,
+        id: u64,
+        eval: Evaluation,
+    },
+    // `pre_evaluate` finished a chunk, times in seconds
+    Checkpoint {
+        done: usize,
+        total: usize,
+        elapsed: f32,
+        eta: f32,
+    },
+}
+
+pub type Progress = dyn Fn(&Report) + Send + Sync;
+
 #[derive(Clone)]
 pub struct Search {
-    arc: Arc<(Mutex<Store>, Condvar)>,
+    cache: Arc<Cache>,
+    // Read-only book below the cache, new results only go to the cache
+    book: Option<Arc<Book>>,
+    table: Arc<Table>,
+    threads: usize,
+    progress: Option<Arc<Progress>>,
+    // Written by `flush`, None if the cache is never saved
+    scorebook: Option<PathBuf>,
 }
 
 impl Search {
     pub fn new() -> Search {
-        let bytes = fs::read("scorebook").unwrap_or(Vec::new());
-        let cache: HashMap<u64, Evaluation> =
-            bincode::deserialize(&bytes).unwrap_or(HashMap::new());
+        return Search::open(SCOREBOOK);
+    }
+
+    // Loads the scorebook at `path`, a missing or unreadable file gives an
+    // empty cache. `flush` writes back to `path`.
+    pub fn open(path: impl AsRef<Path>) -> Search {
+        return Search::with_cache(None, path.as_ref());
+    }
+
+    // Searches on top of the memory-mapped book at `path`, the scorebook
+    // file is still loaded as the writable overlay
+    pub fn with_book(path: impl AsRef<Path>) -> io::Result<Search> {
+        let book = Book::open(path)?;
+        println!("Book opened: {} entries", book.len());
+        return Ok(Search::with_cache(Some(book), Path::new(SCOREBOOK)));
+    }
+
+    // Starts without any stored evaluations, reads and writes nothing
+    pub fn empty() -> Search {
+        return Search::with_entries(None, HashMap::new(), None);
+    }
+
+    fn with_cache(book: Option<Book>, scorebook: &Path) -> Search {
+        let cache = load_scorebook(scorebook).unwrap_or_default();
         println!("Search loaded: {} entries", cache.len());
+        return Search::with_entries(book, cache, Some(scorebook.to_path_buf()));
+    }
+
+    fn with_entries(
+        book: Option<Book>,
+        cache: HashMap<u64, Evaluation>,
+        scorebook: Option<PathBuf>,
+    ) -> Search {
         return Search {
-            arc: Arc::new((
-                Mutex::new(Store {
-                    cache,
-                    eval: HashSet::new(),
-                }),
-                Condvar::new(),
-            )),
+            cache: Arc::new(Cache::new(cache)),
+            book: book.map(Arc::new),
+            table: Arc::new(Table::new(TABLE_BITS)),
+            threads: 1,
+            progress: None,
+            scorebook,
+        };
+    }
+
+    // Threads used by a single `evaluate`, 1 keeps the deterministic
+    // single threaded search
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    pub fn threads(&self) -> usize {
+        return self.threads;
+    }
+
+    // Shared by all clones of this `Search`, see `Cache::set_memory_limit`
+    pub fn set_memory_limit(&self, bytes: Option<usize>) {
+        self.cache.set_memory_limit(bytes);
+    }
+
+    pub fn cache_stats(&self) -> CacheStats {
+        return self.cache.stats();
+    }
+
+    // Called from the searching threads, send to a channel to render the
+    // reports elsewhere
+    pub fn set_progress(&mut self, progress: impl Fn(&Report) + Send + Sync + 'static) {
+        self.progress = Some(Arc::new(progress));
+    }
+
+    pub fn clear_progress(&mut self) {
+        self.progress = None;
+    }
+
+    fn report(&self, report: &Report) {
+        if let Some(progress) = &self.progress {
+            progress(report);
+        }
+    }
+
+    // Cached evaluation of `id` if `done` accepts it, otherwise `id` is
+    // claimed by the caller until `finish`
+    fn begin(&self, id: u64, done: impl Fn(&Evaluation) -> bool) -> Option<Evaluation> {
+        let booked = self.book.as_ref().and_then(|book| book.get(id));
+        let mut eval = match booked.filter(&done) {
+            Some(e) => e,
+            None => self.cache.begin(id, done)?,
+        };
+        eval.nodes = 0;
+        eval.time = 0.0;
+        return Some(eval);
+    }
+
+    fn finish(&self, id: u64, eval: Option<Evaluation>) {
+        self.cache.finish(id, eval);
+    }
+
+    // Best known evaluation from the cache or the book
+    fn get(&self, id: u64) -> Option<Evaluation> {
+        let cached = self.cache.get(id);
+        let booked = self.book.as_ref().and_then(|book| book.get(id));
+        return match (cached, booked) {
+            (Some(c), Some(b)) => Some(if priority(&b) > priority(&c) { b } else { c }),
+            (c, b) => c.or(b),
         };
     }
 
     pub fn evaluate(&mut self, b: &GameBoard, max_depth: i32) -> Evaluation {
+        return self.evaluate_board(b.get_board(), max_depth);
+    }
+
+    pub fn evaluate_board(&mut self, b: &Board, max_depth: i32) -> Evaluation {
+        return self.evaluate_until(b, max_depth, None).unwrap();
+    }
+
+    // None if `abort` got set before the evaluation finished
+    fn evaluate_until(
+        &self,
+        b: &Board,
+        max_depth: i32,
+        abort: Option<&AtomicBool>,
+    ) -> Option<Evaluation> {
+        // Ids leave out pieces taken out of play, so such positions are
+        // searched apart from the cache, the book and the shared table
+        if !b.has_full_reserves() {
+            return self.search(b, max_depth, abort, false);
+        }
         let id = b.get_max_id();
-        let mut guard = self.arc.0.lock().unwrap();
-        if let Some(_) = guard.eval.get(&id) {
-            loop {
-                guard = self.arc.1.wait(guard).unwrap();
-                if guard.eval.get(&id) == None {
-                    break;
-                }
-            }
+        let done = |e: &Evaluation| e.kind != EvalKind::TooFar || e.depth >= max_depth as u16;
+        if let Some(eval) = self.begin(id, done) {
+            return Some(eval);
         }
-        if let Some(e) = guard.cache.get(&id) {
-            if e.kind != EvalKind::TooFar || e.depth >= max_depth as u16 {
-                let mut eval = *e;
-                eval.nodes = 0;
-                eval.time = 0.0;
-                return eval;
+
+        let eval = self.search(b, max_depth, abort, true);
+
+        self.finish(id, eval);
+        return eval;
+    }
+
+    // Iterative deepening on the configured threads, `shared` lets them use
+    // the table shared with other searches
+    fn search(
+        &self,
+        b: &Board,
+        max_depth: i32,
+        abort: Option<&AtomicBool>,
+        shared: bool,
+    ) -> Option<Evaluation> {
+        let progress = self.progress.as_deref();
+        let eval = match self.threads {
+            1 => deepening(b, max_depth, abort, progress),
+            n if shared => smp::deepening(b, max_depth, n, &self.table, abort, progress),
+            n => {
+                let tt = Table::new(PRIVATE_TABLE_BITS);
+                smp::deepening(b, max_depth, n, &tt, abort, progress)
             }
+        };
+        let aborted = abort.is_some_and(|a| a.load(Ordering::Relaxed));
+        return (!aborted).then_some(eval);
+    }
+
+    // Evaluates the positions after each reply to `b` in the background,
+    // `expected` first, so evaluating them later is a cache hit
+    pub fn ponder(&self, b: &GameBoard, max_depth: i32, expected: Option<Move>) -> Ponder {
+        let mut replies = b.get_moves();
+        if let Some(i) = expected.and_then(|m| replies.iter().position(|&r| r == m)) {
+            replies[..=i].rotate_right(1);
         }
-        guard.eval.insert(id);
-        drop(guard);
+        let search = self.clone();
+        let b = *b.get_board();
+        let abort = Arc::new(AtomicBool::new(false));
+        let flag = abort.clone();
+        let handle = thread::spawn(move || {
+            for m in replies {
+                let mut next = b;
+                next.do_move(m);
+                search.evaluate_until(&next, max_depth, Some(&flag));
+                if flag.load(Ordering::Relaxed) {
+                    return;
+                }
+            }
+        });
+        return Ponder {
+            abort,
+            handle: Some(handle),
+        };
+    }
 
-        let eval = deepening(b, max_depth);
+    // Proof-number search without depth horizon, gives up after creating
+    // `max_nodes` nodes. Only conclusive results go into the cache.
+    pub fn prove(&mut self, b: &GameBoard, max_nodes: u64) -> Evaluation {
+        if !b.get_board().has_full_reserves() {
+            return pns::prove(b.get_board(), max_nodes);
+        }
+        let id = b.get_max_id();
+        if let Some(eval) = self.begin(id, |e| e.kind != EvalKind::TooFar) {
+            return eval;
+        }
 
-        let mut guard = self.arc.0.lock().unwrap();
-        guard.cache.insert(id, eval);
-        guard.eval.remove(&id);
-        self.arc.1.notify_all();
-        drop(guard);
+        let eval = pns::prove(b.get_board(), max_nodes);
 
+        let conclusive = eval.kind != EvalKind::TooFar;
+        self.finish(id, conclusive.then_some(eval));
         return eval;
     }
 
     pub fn pre_evaluate(&mut self, depth: i32, max_depth: i32) {
-        let mut board = GameBoard::new(true);
-        let mut entries = Vec::new();
-        let mut count = 0;
-        let id = AtomicUsize::new(0);
-        self.add_entries(&mut board, depth, max_depth, &mut count, &mut entries);
-        println!("Count {}, added {}", count, entries.len());
-        entries.par_iter_mut().for_each(|(s, b)| {
-            let i = id.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-            println!("eval {}: {}", i, b.get_max_id());
-            let e = s.evaluate(b, max_depth);
-            if e.depth != 10 {
-                println!("done {}: {:?}", i, e);
-            }
-        });
+        Job::new(self, depth, max_depth)
+            .run(self, None, None)
+            .unwrap();
     }
 
-    fn add_entries(
+    // Like `pre_evaluate`, but checkpoints to `path` and continues the job
+    // found there after an interruption. Once the job is finished the cache
+    // is flushed and the checkpoint removed.
+    pub fn resume_pre_evaluate(
         &mut self,
-        b: &mut GameBoard,
+        path: impl AsRef<Path>,
         depth: i32,
         max_depth: i32,
-        count: &mut u64,
-        entries: &mut Vec<(Search, GameBoard)>,
-    ) {
-        *count += 1;
-        let guard = self.arc.0.lock().unwrap();
-        'blk: {
-            if let Some(e) = guard.cache.get(&b.get_max_id()) {
-                if e.kind != EvalKind::TooFar || e.depth >= max_depth as u16 {
-                    break 'blk;
-                }
+    ) -> io::Result<()> {
+        let path = path.as_ref();
+        let mut job = match Job::load(path) {
+            Ok(job) if job.depth == depth && job.max_depth == max_depth => job,
+            Ok(_) => {
+                let msg = "checkpoint is for another depth";
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, msg));
             }
-            entries.push((self.clone(), b.clone()));
-        }
-        drop(guard);
-        if b.get_state() != State::InGame || depth == 0 {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let job = Job::new(self, depth, max_depth);
+                job.save(path)?;
+                job
+            }
+            Err(e) => return Err(e),
+        };
+        job.run(self, Some(path), None)?;
+        self.flush();
+        fs::remove_file(Job::journal(path))?;
+        return fs::remove_file(path);
+    }
+
+    // Merges the cache into the scorebook file. Entries the memory limit
+    // evicted are only gone from memory, the file keeps them.
+    pub fn flush(&self) {
+        let Some(path) = &self.scorebook else {
             return;
+        };
+        let mut entries = match load_scorebook(path) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            entries => entries.unwrap(),
+        };
+        self.merge_cache(&mut entries);
+        save_scorebook(path, &entries).unwrap();
+        println!("Search saved: {} entries", entries.len());
+    }
+
+    // Adds the cached entries to `entries` unless those hold a better one
+    fn merge_cache(&self, entries: &mut HashMap<u64, Evaluation>) {
+        for (id, e) in self.cache.entries() {
+            let keep = entries.get(&id).is_some_and(|b| priority(b) > priority(&e));
+            if !keep {
+                entries.insert(id, e);
+            }
+        }
+    }
+
+    // Writes the book merged with the cache to `path`, which may be the
+    // book currently opened
+    pub fn write_book(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut entries: HashMap<u64, Evaluation> = match &self.book {
+            Some(book) => book.iter().collect(),
+            None => HashMap::new(),
+        };
+        self.merge_cache(&mut entries);
+        println!("Book written: {} entries", entries.len());
+        return Book::write(path, entries);
+    }
+}
+
+// Reads a scorebook, files without the header are from before canonical
+// ids and get migrated
+pub fn load_scorebook(path: impl AsRef<Path>) -> io::Result<HashMap<u64, Evaluation>> {
+    let bytes = fs::read(path)?;
+    let invalid = |e| io::Error::new(io::ErrorKind::InvalidData, e);
+    return match bytes.strip_prefix(SCOREBOOK_MAGIC) {
+        Some(rest) => bincode::deserialize(rest).map_err(invalid),
+        None => {
+            let old = bincode::deserialize(&bytes).map_err(invalid)?;
+            Ok(migrate_scorebook(old))
         }
-        for m in b.get_moves() {
-            b.do_move(m);
-            self.add_entries(b, depth - 1, max_depth, count, entries);
-            b.undo_move();
+    };
+}
+
+// Writes next to `path` and renames into place, so a crash while saving
+// leaves the previous scorebook intact
+pub fn save_scorebook(
+    path: impl AsRef<Path>,
+    entries: &HashMap<u64, Evaluation>,
+) -> io::Result<()> {
+    let mut bytes = SCOREBOOK_MAGIC.to_vec();
+    bincode::serialize_into(&mut bytes, entries).unwrap();
+    let path = path.as_ref();
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, bytes)?;
+    return fs::rename(tmp, path);
+}
+
+// Scorebooks written before ids were canonical keyed a position by its
+// unrotated layers, the number of moves played and the player. Rekeys such
+// entries by `Board::get_max_id`, merged entries keep the best result and
+// the one with the lowest old id among equally good ones.
+pub fn migrate_scorebook(old: HashMap<u64, Evaluation>) -> HashMap<u64, Evaluation> {
+    let mut old: Vec<(u64, Evaluation)> = old.into_iter().collect();
+    old.sort_unstable_by_key(|e| e.0);
+    let mut entries = HashMap::new();
+    for (id, e) in old {
+        let layers = id >> 9;
+        let id = Board::from_id(layers << 1 | id & 1).get_max_id();
+        let keep = entries
+            .get(&id)
+            .is_some_and(|k| priority(k) >= priority(&e));
+        if !keep {
+            entries.insert(id, e);
         }
     }
+    return entries;
+}
 
-    pub fn flush(&self) {
-        let guard = self.arc.0.lock().unwrap();
-        let bytes = bincode::serialize(&guard.cache).unwrap();
-        fs::write("scorebook", bytes).unwrap();
-        println!("Search saved: {} entries", guard.cache.len());
+// Background search started by `Search::ponder`, stops when dropped
+pub struct Ponder {
+    abort: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Ponder {
+    pub fn is_finished(&self) -> bool {
+        return match &self.handle {
+            Some(handle) => handle.is_finished(),
+            None => true,
+        };
+    }
+
+    // Aborts the running evaluation and waits for the thread to exit
+    pub fn stop(&mut self) {
+        self.abort.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            handle.join().unwrap();
+        }
+    }
+}
+
+impl Drop for Ponder {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl Default for Search {
+    fn default() -> Self {
+        return Self::new();
     }
 }
@@ -0,0 +1,124 @@
+use std::{cmp::Ordering, fs, fs::File, io, path::Path};
+
+use memmap2::Mmap;
+
+use super::{EvalKind, Evaluation};
+
+const MAGIC: &[u8; 8] = b"GOBBOOK1";
+const HEADER: usize = 16;
+// id, kind, padding, depth, time, nodes
+const RECORD: usize = 24;
+
+// Read-only scorebook: a header followed by fixed-width records sorted by
+// id. The file is memory-mapped and binary searched in place, so processes
+// opening the same book share its pages and load it instantly.
+pub struct Book {
+    map: Mmap,
+    len: usize,
+}
+
+fn invalid(msg: &str) -> io::Error {
+    return io::Error::new(io::ErrorKind::InvalidData, msg);
+}
+
+impl Book {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Book> {
+        let file = File::open(path)?;
+        // Safety: books are never modified in place, `write` replaces the
+        // file through a rename so existing mappings stay valid
+        let map = unsafe { Mmap::map(&file)? };
+        if map.len() < HEADER || &map[..8] != MAGIC {
+            return Err(invalid("not a book"));
+        }
+        let len = u64::from_le_bytes(map[8..16].try_into().unwrap());
+        let size = usize::try_from(len)
+            .ok()
+            .and_then(|len| len.checked_mul(RECORD))
+            .and_then(|records| records.checked_add(HEADER));
+        if size != Some(map.len()) {
+            return Err(invalid("truncated book"));
+        }
+        return Ok(Book {
+            map,
+            len: len as usize,
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        return self.len;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.len == 0;
+    }
+
+    fn record(&self, idx: usize) -> &[u8] {
+        let start = HEADER + idx * RECORD;
+        return &self.map[start..start + RECORD];
+    }
+
+    fn id(&self, idx: usize) -> u64 {
+        return u64::from_le_bytes(self.record(idx)[..8].try_into().unwrap());
+    }
+
+    // None for a kind byte this version doesn't know, such records are
+    // skipped like missing ones
+    fn eval(&self, idx: usize) -> Option<Evaluation> {
+        let r = self.record(idx);
+        let kind = match r[8] {
+            0 => EvalKind::TooFar,
+            1 => EvalKind::Loss,
+            2 => EvalKind::Draw,
+            3 => EvalKind::Win,
+            _ => return None,
+        };
+        return Some(Evaluation {
+            kind,
+            depth: u16::from_le_bytes(r[10..12].try_into().unwrap()),
+            time: f32::from_le_bytes(r[12..16].try_into().unwrap()),
+            nodes: u64::from_le_bytes(r[16..24].try_into().unwrap()),
+        });
+    }
+
+    pub fn get(&self, id: u64) -> Option<Evaluation> {
+        let (mut lo, mut hi) = (0, self.len);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            match self.id(mid).cmp(&id) {
+                Ordering::Less => lo = mid + 1,
+                Ordering::Greater => hi = mid,
+                Ordering::Equal => return self.eval(mid),
+            }
+        }
+        return None;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u64, Evaluation)> + '_ {
+        return (0..self.len).filter_map(|i| Some((self.id(i), self.eval(i)?)));
+    }
+
+    // Writes a book next to `path` and renames it into place
+    pub fn write(
+        path: impl AsRef<Path>,
+        entries: impl IntoIterator<Item = (u64, Evaluation)>,
+    ) -> io::Result<()> {
+        let mut entries: Vec<(u64, Evaluation)> = entries.into_iter().collect();
+        entries.sort_unstable_by_key(|e| e.0);
+        entries.dedup_by_key(|e| e.0);
+        let mut bytes = Vec::with_capacity(HEADER + entries.len() * RECORD);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&(entries.len() as u64).to_le_bytes());
+        for (id, e) in entries {
+            bytes.extend_from_slice(&id.to_le_bytes());
+            bytes.push(e.kind as u8);
+            bytes.push(0);
+            bytes.extend_from_slice(&e.depth.to_le_bytes());
+            bytes.extend_from_slice(&e.time.to_le_bytes());
+            bytes.extend_from_slice(&e.nodes.to_le_bytes());
+        }
+        let path = path.as_ref();
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, bytes)?;
+        return fs::rename(tmp, path);
+    }
+}
@@ -0,0 +1,204 @@
+use std::{
+    collections::HashMap,
+    mem::size_of,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Condvar, Mutex,
+    },
+};
+
+use super::{EvalKind, Evaluation};
+
+const SHARD_BITS: u32 = 6;
+// Rough heap size of one entry including the HashMap slack
+pub const ENTRY_BYTES: usize = (size_of::<u64>() + size_of::<Evaluation>()) * 8 / 7 + 1;
+// Share of a full shard evicted at once, in percent
+const EVICT_PERCENT: usize = 10;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub entries: usize,
+    // Entry limit, 0 if unbounded
+    pub capacity: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    // Evicted Win/Loss/Draw entries, only once no TooFar entry was left
+    pub evicted_decisive: u64,
+}
+
+// Entries with lower priority are evicted first: TooFar before conclusive
+// results, shallow before deep ones
+pub(super) fn priority(e: &Evaluation) -> (bool, u16) {
+    return (e.kind != EvalKind::TooFar, e.depth);
+}
+
+// Signalled once the evaluation of a claimed position is finished
+#[derive(Default)]
+struct Flight {
+    done: Mutex<bool>,
+    cv: Condvar,
+}
+
+#[derive(Default)]
+struct Shard {
+    cache: HashMap<u64, Evaluation>,
+    eval: HashMap<u64, Arc<Flight>>,
+}
+
+// Evaluation cache split into independently locked shards. A position being
+// evaluated is claimed, other threads asking for it wait on that position
+// only instead of a condition variable shared by all positions.
+pub struct Cache {
+    shards: Vec<Mutex<Shard>>,
+    // Entries per shard, 0 if unbounded
+    limit: AtomicUsize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    evicted_decisive: AtomicU64,
+}
+
+impl Cache {
+    pub fn new(entries: HashMap<u64, Evaluation>) -> Cache {
+        let cache = Cache {
+            shards: (0..1 << SHARD_BITS)
+                .map(|_| Mutex::new(Shard::default()))
+                .collect(),
+            limit: AtomicUsize::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            evicted_decisive: AtomicU64::new(0),
+        };
+        for (id, e) in entries {
+            cache.insert(id, e);
+        }
+        return cache;
+    }
+
+    fn shard(&self, id: u64) -> &Mutex<Shard> {
+        let idx = id.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> (64 - SHARD_BITS);
+        return &self.shards[idx as usize];
+    }
+
+    pub fn get(&self, id: u64) -> Option<Evaluation> {
+        return self.shard(id).lock().unwrap().cache.get(&id).copied();
+    }
+
+    pub fn insert(&self, id: u64, e: Evaluation) {
+        let mut shard = self.shard(id).lock().unwrap();
+        shard.cache.insert(id, e);
+        self.trim(&mut shard);
+    }
+
+    // Caps the cache at roughly `bytes` of memory, None removes the cap
+    pub fn set_memory_limit(&self, bytes: Option<usize>) {
+        let limit = match bytes {
+            Some(bytes) => ((bytes / ENTRY_BYTES) >> SHARD_BITS).max(1),
+            None => 0,
+        };
+        self.limit.store(limit, Ordering::Relaxed);
+        for shard in &self.shards {
+            self.trim(&mut shard.lock().unwrap());
+        }
+    }
+
+    fn trim(&self, shard: &mut Shard) {
+        let limit = self.limit.load(Ordering::Relaxed);
+        if limit == 0 || shard.cache.len() <= limit {
+            return;
+        }
+        // Evict a batch so a full shard doesn't sort on every insert
+        let keep = limit - limit * EVICT_PERCENT / 100;
+        let mut order: Vec<(bool, u16, u64)> = shard
+            .cache
+            .iter()
+            .map(|(&id, e)| {
+                let (decisive, depth) = priority(e);
+                (decisive, depth, id)
+            })
+            .collect();
+        let evict = order.len() - keep;
+        order.select_nth_unstable(evict - 1);
+        for &(decisive, _, id) in &order[..evict] {
+            shard.cache.remove(&id);
+            if decisive {
+                self.evicted_decisive.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.evictions.fetch_add(evict as u64, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let limit = self.limit.load(Ordering::Relaxed);
+        return CacheStats {
+            entries: self.len(),
+            capacity: limit << SHARD_BITS,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            evicted_decisive: self.evicted_decisive.load(Ordering::Relaxed),
+        };
+    }
+
+    pub fn len(&self) -> usize {
+        return self
+            .shards
+            .iter()
+            .map(|s| s.lock().unwrap().cache.len())
+            .sum();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.len() == 0;
+    }
+
+    pub fn entries(&self) -> HashMap<u64, Evaluation> {
+        let mut entries = HashMap::new();
+        for shard in &self.shards {
+            entries.extend(shard.lock().unwrap().cache.iter());
+        }
+        return entries;
+    }
+
+    // Waits while another thread evaluates `id`, returns the cached
+    // evaluation if `done` accepts it and otherwise claims `id`
+    pub fn begin(&self, id: u64, done: impl Fn(&Evaluation) -> bool) -> Option<Evaluation> {
+        loop {
+            let mut shard = self.shard(id).lock().unwrap();
+            if let Some(flight) = shard.eval.get(&id).cloned() {
+                drop(shard);
+                let mut finished = flight.done.lock().unwrap();
+                while !*finished {
+                    finished = flight.cv.wait(finished).unwrap();
+                }
+                continue;
+            }
+            if let Some(e) = shard.cache.get(&id) {
+                if done(e) {
+                    self.hits.fetch_add(1, Ordering::Relaxed);
+                    return Some(*e);
+                }
+            }
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            shard.eval.insert(id, Arc::default());
+            return None;
+        }
+    }
+
+    // Releases the claim on `id`, storing `eval` if there is one
+    pub fn finish(&self, id: u64, eval: Option<Evaluation>) {
+        let mut shard = self.shard(id).lock().unwrap();
+        if let Some(eval) = eval {
+            shard.cache.insert(id, eval);
+            self.trim(&mut shard);
+        }
+        let flight = shard.eval.remove(&id);
+        drop(shard);
+        if let Some(flight) = flight {
+            *flight.done.lock().unwrap() = true;
+            flight.cv.notify_all();
+        }
+    }
+}
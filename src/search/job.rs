@@ -0,0 +1,167 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::Instant,
+};
+
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::{positions::Positions, Board};
+
+use super::{EvalKind, Evaluation, Report, Search};
+
+// Positions evaluated between two checkpoints
+const CHUNK: usize = 256;
+
+// Precomputation of all positions up to `depth` plies. Positions are
+// queued once per symmetry class by id, `done` counts the queued positions
+// already evaluated.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Job {
+    pub depth: i32,
+    pub max_depth: i32,
+    queue: Vec<u64>,
+    done: usize,
+}
+
+impl Job {
+    pub fn new(search: &Search, depth: i32, max_depth: i32) -> Job {
+        let mut positions = Positions::new(&Board::new(), depth);
+        let queue = positions
+            .by_ref()
+            .map(|(_, b)| b.get_max_id())
+            .filter(|&id| {
+                !search
+                    .get(id)
+                    .is_some_and(|e| e.kind != EvalKind::TooFar || e.depth >= max_depth as u16)
+            })
+            .collect();
+        let job = Job {
+            depth,
+            max_depth,
+            queue,
+            done: 0,
+        };
+        search.report(&Report::Queued {
+            count: positions.seen() as u64,
+            queued: job.queue.len(),
+        });
+        return job;
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Job> {
+        let bytes = fs::read(path)?;
+        return bincode::deserialize(&bytes)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e));
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let bytes = bincode::serialize(self).unwrap();
+        let path = path.as_ref();
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, bytes)?;
+        return fs::rename(tmp, path);
+    }
+
+    pub fn done(&self) -> usize {
+        return self.done;
+    }
+
+    pub fn total(&self) -> usize {
+        return self.queue.len();
+    }
+
+    pub fn is_finished(&self) -> bool {
+        return self.done >= self.queue.len();
+    }
+
+    // Evaluations of the job checkpointed to `path`, kept next to it
+    pub fn journal(path: impl AsRef<Path>) -> PathBuf {
+        return path.as_ref().with_extension("log");
+    }
+
+    // Puts the evaluations a run before an interruption saved back into
+    // `search`. A record cut short by a crash is cut off the journal,
+    // checkpoints from before the journal have none and flushed the cache.
+    fn restore(search: &Search, journal: &Path) -> io::Result<()> {
+        let bytes = match fs::read(journal) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            bytes => bytes?,
+        };
+        let mut rest = &bytes[..];
+        while !rest.is_empty() {
+            let record = rest;
+            match bincode::deserialize_from::<_, (u64, Evaluation)>(&mut rest) {
+                Ok((id, eval)) => search.cache.insert(id, eval),
+                Err(_) => {
+                    let file = OpenOptions::new().write(true).open(journal)?;
+                    return file.set_len((bytes.len() - record.len()) as u64);
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    // Evaluates the remaining positions, or stops after the current chunk
+    // once `stop` is set. With a `checkpoint` path each chunk's evaluations
+    // are appended to the journal and the job saved, so an interrupted run
+    // continues from the last chunk when loaded again.
+    pub fn run(
+        &mut self,
+        search: &Search,
+        checkpoint: Option<&Path>,
+        stop: Option<&AtomicBool>,
+    ) -> io::Result<()> {
+        let mut journal = match checkpoint.map(Job::journal) {
+            Some(journal) if self.done > 0 => {
+                Job::restore(search, &journal)?;
+                let file = OpenOptions::new().create(true).append(true).open(journal)?;
+                Some(BufWriter::new(file))
+            }
+            Some(journal) => Some(BufWriter::new(File::create(journal)?)),
+            None => None,
+        };
+        let start = Instant::now();
+        let first = self.done;
+        let total = self.total();
+        let index = AtomicUsize::new(self.done);
+        while !self.is_finished() && !stop.is_some_and(|s| s.load(Ordering::Relaxed)) {
+            let end = (self.done + CHUNK).min(total);
+            let evaluated = Mutex::new(Vec::with_capacity(end - self.done));
+            self.queue[self.done..end].par_iter().for_each(|&id| {
+                let b = Board::from_id(id);
+                let eval = search.evaluate_until(&b, self.max_depth, None).unwrap();
+                evaluated.lock().unwrap().push((id, eval));
+                search.report(&Report::Evaluated {
+                    index: index.fetch_add(1, Ordering::Relaxed),
+                    total,
+                    id,
+                    eval,
+                });
+            });
+            self.done = end;
+            if let (Some(path), Some(journal)) = (checkpoint, &mut journal) {
+                for entry in evaluated.into_inner().unwrap() {
+                    bincode::serialize_into(&mut *journal, &entry).unwrap();
+                }
+                journal.flush()?;
+                journal.get_ref().sync_data()?;
+                self.save(path)?;
+            }
+            let elapsed = start.elapsed().as_secs_f32();
+            let rate = (self.done - first) as f32 / elapsed.max(f32::EPSILON);
+            search.report(&Report::Checkpoint {
+                done: self.done,
+                total,
+                elapsed,
+                eta: (total - self.done) as f32 / rate,
+            });
+        }
+        return Ok(());
+    }
+}
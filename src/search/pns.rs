@@ -0,0 +1,209 @@
+use std::time::Instant;
+
+use crate::Board;
+
+use super::{EvalKind, Evaluation};
+
+const INF: u32 = u32::MAX;
+
+struct Node {
+    b: Board,
+    pn: u32,
+    dn: u32,
+    parent: u32,
+    first: u32,
+    count: u32,
+    depth: u16,
+}
+
+// Proof-number search over an explicit tree. The attacker is the player
+// to move at OR nodes, a position repeating one of its ancestors counts as
+// a failed attack, so proofs never rely on cycles.
+struct Tree {
+    nodes: Vec<Node>,
+    attacker: i32,
+    max_depth: u16,
+}
+
+impl Tree {
+    fn new(b: &Board, attacker: i32) -> Tree {
+        let mut tree = Tree {
+            nodes: Vec::new(),
+            attacker,
+            max_depth: 0,
+        };
+        tree.push(*b, INF, 0);
+        return tree;
+    }
+
+    fn is_or(&self, idx: usize) -> bool {
+        return self.nodes[idx].b.player == self.attacker;
+    }
+
+    fn push(&mut self, b: Board, parent: u32, depth: u16) {
+        let (mut pn, mut dn) = (1, 1);
+        let winner = match b.get_state() {
+            1 => Some(b.player),
+            2 => Some(b.player ^ 1),
+            3 => Some(-1),
+            _ => None,
+        };
+        if let Some(w) = winner {
+            (pn, dn) = if w == self.attacker {
+                (0, INF)
+            } else {
+                (INF, 0)
+            };
+        } else if self.is_repetition(&b, parent) {
+            (pn, dn) = (INF, 0);
+        }
+        self.max_depth = self.max_depth.max(depth);
+        self.nodes.push(Node {
+            b,
+            pn,
+            dn,
+            parent,
+            first: 0,
+            count: 0,
+            depth,
+        });
+    }
+
+    fn is_repetition(&self, b: &Board, mut parent: u32) -> bool {
+        while parent != INF {
+            let node = &self.nodes[parent as usize];
+            if node.b == *b {
+                return true;
+            }
+            parent = node.parent;
+        }
+        return false;
+    }
+
+    fn select(&self) -> usize {
+        let mut idx = 0;
+        while self.nodes[idx].count != 0 {
+            let node = &self.nodes[idx];
+            let children = node.first as usize..(node.first + node.count) as usize;
+            idx = match self.is_or(idx) {
+                true => children.min_by_key(|&c| self.nodes[c].pn).unwrap(),
+                false => children.min_by_key(|&c| self.nodes[c].dn).unwrap(),
+            };
+        }
+        return idx;
+    }
+
+    fn expand(&mut self, idx: usize) {
+        let b = self.nodes[idx].b;
+        let depth = self.nodes[idx].depth + 1;
+        let first = self.nodes.len() as u32;
+        for m in b.get_moves() {
+            let mut child = b;
+            child.do_move(m);
+            self.push(child, idx as u32, depth);
+        }
+        self.nodes[idx].first = first;
+        self.nodes[idx].count = self.nodes.len() as u32 - first;
+        if self.nodes[idx].count == 0 {
+            // No move left, the attacker did not get the line
+            self.nodes[idx].pn = INF;
+            self.nodes[idx].dn = 0;
+        }
+    }
+
+    fn update(&mut self, mut idx: usize) {
+        loop {
+            let node = &self.nodes[idx];
+            if node.count != 0 {
+                let children = node.first as usize..(node.first + node.count) as usize;
+                let pns = children.clone().map(|c| self.nodes[c].pn);
+                let dns = children.map(|c| self.nodes[c].dn);
+                let (pn, dn) = match self.is_or(idx) {
+                    true => (pns.min().unwrap(), dns.fold(0, u32::saturating_add)),
+                    false => (pns.fold(0, u32::saturating_add), dns.min().unwrap()),
+                };
+                self.nodes[idx].pn = pn;
+                self.nodes[idx].dn = dn;
+            }
+            if self.nodes[idx].parent == INF {
+                return;
+            }
+            idx = self.nodes[idx].parent as usize;
+        }
+    }
+
+    // Plies to the attacker's result along the proof found, the defender
+    // taking the longest way. Children come after their parent in `nodes`.
+    fn proof_length(&self) -> u16 {
+        let mut len = vec![0u16; self.nodes.len()];
+        for idx in (0..self.nodes.len()).rev() {
+            let node = &self.nodes[idx];
+            if node.pn != 0 || node.count == 0 {
+                continue;
+            }
+            let proven = (node.first..node.first + node.count)
+                .map(|c| c as usize)
+                .filter(|&c| self.nodes[c].pn == 0)
+                .map(|c| len[c] + 1);
+            len[idx] = match self.is_or(idx) {
+                true => proven.min().unwrap(),
+                false => proven.max().unwrap(),
+            };
+        }
+        return len[0];
+    }
+
+    // Some(true) if the attacker wins, Some(false) if it can't be forced
+    fn solve(&mut self, max_nodes: u64) -> Option<bool> {
+        while self.nodes[0].pn != 0 && self.nodes[0].dn != 0 {
+            if self.nodes.len() as u64 >= max_nodes {
+                return None;
+            }
+            let idx = self.select();
+            self.expand(idx);
+            self.update(idx);
+        }
+        return Some(self.nodes[0].pn == 0);
+    }
+}
+
+// Proves the position without a depth horizon. Draw means neither side can
+// force a line, TooFar that `max_nodes` ran out before a proof was found.
+pub fn prove(b: &Board, max_nodes: u64) -> Evaluation {
+    let start = Instant::now();
+    let mut eval = Evaluation {
+        kind: EvalKind::TooFar,
+        depth: 0,
+        time: 0.0,
+        nodes: 0,
+    };
+    // Like the other evaluations a Win or Loss is as deep as the line to
+    // the result, otherwise the depth is the deepest position reached
+    let mut tree = Tree::new(b, b.player);
+    let win = tree.solve(max_nodes);
+    eval.nodes += tree.nodes.len() as u64;
+    eval.depth = tree.max_depth;
+    match win {
+        Some(true) => {
+            eval.kind = EvalKind::Win;
+            eval.depth = tree.proof_length();
+        }
+        Some(false) => {
+            let mut tree = Tree::new(b, b.player ^ 1);
+            let loss = tree.solve(max_nodes);
+            eval.nodes += tree.nodes.len() as u64;
+            eval.depth = eval.depth.max(tree.max_depth);
+            eval.kind = match loss {
+                Some(true) => EvalKind::Loss,
+                Some(false) => EvalKind::Draw,
+                None => EvalKind::TooFar,
+            };
+            if loss == Some(true) {
+                eval.depth = tree.proof_length();
+            }
+        }
+        None => (),
+    }
+    eval.time = start.elapsed().as_secs_f32();
+    return eval;
+}
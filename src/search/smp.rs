@@ -0,0 +1,114 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicI32, AtomicU64, AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::Instant,
+};
+
+use crate::{Board, Move};
+
+use super::{
+    negamax, table::Table, to_eval, Ctx, Evaluation, Progress, Report, FAR_SCORE, MAX_SCORE,
+    MIN_SCORE, WIN_SCORE,
+};
+
+// Root splitting: the root moves are handed out to the threads one by one
+// and searched with the best score found so far as alpha. All threads share
+// the lock-free table so transpositions between root moves are reused.
+pub fn deepening(
+    b: &Board,
+    max_depth: i32,
+    threads: usize,
+    tt: &Table,
+    abort: Option<&AtomicBool>,
+    progress: Option<&Progress>,
+) -> Evaluation {
+    let mut moves = b.get_moves();
+    // Moves onto winning spots first, as negamax does
+    moves.sort_by_key(|m| !b.is_winning_spot(m.to));
+    let mut total = Ctx::new(Some(tt), None);
+    total.abort = abort;
+    let mut depth = 0;
+    let start = Instant::now();
+    loop {
+        let score = match b.get_state() != 0 || depth == 0 {
+            true => {
+                let mut search_b = *b;
+                total.root_depth = depth;
+                negamax(&mut search_b, MIN_SCORE, MAX_SCORE, depth, &mut total)
+            }
+            false => split(b, &moves, depth, threads, &mut total),
+        };
+        if total.stopped() {
+            let time = start.elapsed().as_secs_f32();
+            return to_eval(FAR_SCORE, depth, time, total.nodes);
+        }
+        if let Some(progress) = progress {
+            progress(&Report::Iteration(total.iteration(depth, score, &start)));
+        }
+        if score != FAR_SCORE || depth >= max_depth {
+            let time = start.elapsed().as_secs_f32();
+            return to_eval(score, depth, time, total.nodes);
+        }
+        depth += 1;
+    }
+}
+
+fn split(b: &Board, moves: &[Move], depth: i32, threads: usize, total: &mut Ctx) -> i32 {
+    let beta = WIN_SCORE + depth - 1;
+    // Best score and move change together under the lock, `alpha` is only a
+    // copy the threads read without locking
+    let best = Mutex::new((MIN_SCORE, None));
+    let alpha = AtomicI32::new(MIN_SCORE);
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let counters = [(); 4].map(|_| AtomicU64::new(0));
+    thread::scope(|s| {
+        for _ in 0..threads {
+            s.spawn(|| {
+                let mut ctx = Ctx::new(total.tt, Some(&stop));
+                ctx.abort = total.abort;
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= moves.len() || ctx.stopped() {
+                        break;
+                    }
+                    let m = moves[i];
+                    let mut search_b = *b;
+                    search_b.do_move(m);
+                    let a = alpha.load(Ordering::Relaxed);
+                    let mut score = negamax(&mut search_b, -beta, -a, depth - 1, &mut ctx);
+                    if ctx.stopped() {
+                        break;
+                    }
+                    if score != FAR_SCORE {
+                        score = -score;
+                    }
+                    let mut guard = best.lock().unwrap();
+                    if score > guard.0 {
+                        *guard = (score, Some(m));
+                        alpha.store(score, Ordering::Relaxed);
+                    }
+                    drop(guard);
+                    if score >= beta {
+                        stop.store(true, Ordering::Relaxed);
+                    }
+                }
+                let local = [ctx.nodes, ctx.tt_probes, ctx.tt_hits, ctx.cutoffs];
+                for (c, n) in counters.iter().zip(local) {
+                    c.fetch_add(n, Ordering::Relaxed);
+                }
+            });
+        }
+    });
+    let [nodes, tt_probes, tt_hits, cutoffs] = counters.map(|c| c.into_inner());
+    total.nodes += nodes + 1;
+    total.tt_probes += tt_probes;
+    total.tt_hits += tt_hits;
+    total.cutoffs += cutoffs;
+    let (score, m) = best.into_inner().unwrap();
+    total.best = m;
+    return score;
+}
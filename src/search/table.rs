@@ -0,0 +1,68 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::Board;
+
+pub const BOUND_EXACT: u8 = 0;
+pub const BOUND_LOWER: u8 = 1;
+pub const BOUND_UPPER: u8 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    pub score: i32,
+    pub depth: i32,
+    pub bound: u8,
+}
+
+// Lockless transposition table: every slot stores `key ^ data` next to
+// `data`, so a torn write from a racing thread just fails the key check.
+pub struct Table {
+    slots: Vec<(AtomicU64, AtomicU64)>,
+    mask: usize,
+}
+
+impl Table {
+    // `bits` is the log2 of the slot count, each slot takes 16 bytes
+    pub fn new(bits: u32) -> Table {
+        let len = 1usize << bits;
+        let mut slots = Vec::with_capacity(len);
+        for _ in 0..len {
+            slots.push((AtomicU64::new(0), AtomicU64::new(0)));
+        }
+        return Table {
+            slots,
+            mask: len - 1,
+        };
+    }
+
+    pub fn key(b: &Board) -> u64 {
+        // Zero marks an empty slot
+        return b.get_id() + 1;
+    }
+
+    fn index(&self, key: u64) -> usize {
+        return (key.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32) as usize & self.mask;
+    }
+
+    pub fn probe(&self, key: u64) -> Option<Entry> {
+        let slot = &self.slots[self.index(key)];
+        let check = slot.0.load(Ordering::Relaxed);
+        let data = slot.1.load(Ordering::Relaxed);
+        if check ^ data != key {
+            return None;
+        }
+        return Some(Entry {
+            score: data as u16 as i16 as i32,
+            depth: (data >> 16) as u8 as i32,
+            bound: (data >> 24) as u8,
+        });
+    }
+
+    pub fn store(&self, key: u64, e: Entry) {
+        let data = (e.score as i16 as u16 as u64)
+            | ((e.depth as u8 as u64) << 16)
+            | ((e.bound as u64) << 24);
+        let slot = &self.slots[self.index(key)];
+        slot.0.store(key ^ data, Ordering::Relaxed);
+        slot.1.store(data, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,159 @@
+use crate::{Board, GameBoard};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetupError {
+    // Position, size or player that does not exist
+    OffBoard,
+    // The cell already holds a piece of that size
+    Taken,
+    Empty,
+    NoneLeft,
+    // More than two pieces of one size for a player
+    TooMany,
+    // Layers outside the board or two pieces of one size on a cell
+    BadBoard,
+    // Someone already has a line
+    GameOver,
+}
+
+// Position editor. Pieces placed come out of the reserve, pieces removed
+// go back to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Setup {
+    b: Board,
+}
+
+fn check_piece(player: i32, size: i32, pos: i32) -> Result<(), SetupError> {
+    if !(0..2).contains(&player) || !(0..3).contains(&size) || !(0..9).contains(&pos) {
+        return Err(SetupError::OffBoard);
+    }
+    return Ok(());
+}
+
+// Everything but the state, a position that can't arise in a game is fine
+pub(crate) fn check_board(b: &Board) -> Result<(), SetupError> {
+    if !(0..2).contains(&b.player) {
+        return Err(SetupError::OffBoard);
+    }
+    for size in 0..3 {
+        if b.layers[size] & b.layers[size + 3] != 0 {
+            return Err(SetupError::BadBoard);
+        }
+    }
+    for i in 0..6 {
+        if b.layers[i] & !0b111111111 != 0 || b.pieces[i] < 0 {
+            return Err(SetupError::BadBoard);
+        }
+        if b.pieces[i] + b.layers[i].count_ones() as i32 > 2 {
+            return Err(SetupError::TooMany);
+        }
+    }
+    return Ok(());
+}
+
+impl Setup {
+    // Empty board with all pieces in reserve
+    pub fn new() -> Setup {
+        return Setup { b: Board::new() };
+    }
+
+    pub fn from_board(b: Board) -> Result<Setup, SetupError> {
+        check_board(&b)?;
+        return Ok(Setup { b });
+    }
+
+    // Starts from the current position of `g`
+    pub fn from_game(g: &GameBoard) -> Setup {
+        return Setup { b: *g.get_board() };
+    }
+
+    pub fn get_board(&self) -> &Board {
+        return &self.b;
+    }
+
+    // Owner of the piece of `size` on `pos`
+    pub fn get_piece(&self, size: i32, pos: i32) -> Option<i32> {
+        check_piece(0, size, pos).ok()?;
+        return (0..2).find(|p| self.b.layers[(p * 3 + size) as usize] & (1 << pos) != 0);
+    }
+
+    pub fn place(&mut self, player: i32, size: i32, pos: i32) -> Result<(), SetupError> {
+        check_piece(player, size, pos)?;
+        if self.get_piece(size, pos).is_some() {
+            return Err(SetupError::Taken);
+        }
+        let i = (player * 3 + size) as usize;
+        if self.b.pieces[i] == 0 {
+            return Err(SetupError::NoneLeft);
+        }
+        self.b.pieces[i] -= 1;
+        self.b.layers[i] |= 1 << pos;
+        return Ok(());
+    }
+
+    // Returns the owner of the removed piece
+    pub fn remove(&mut self, size: i32, pos: i32) -> Result<i32, SetupError> {
+        check_piece(0, size, pos)?;
+        let Some(player) = self.get_piece(size, pos) else {
+            return Err(SetupError::Empty);
+        };
+        let i = (player * 3 + size) as usize;
+        self.b.layers[i] ^= 1 << pos;
+        self.b.pieces[i] += 1;
+        return Ok(player);
+    }
+
+    // Fewer than the pieces not on the board takes the rest out of play
+    pub fn set_reserve(&mut self, player: i32, size: i32, count: i32) -> Result<(), SetupError> {
+        check_piece(player, size, 0)?;
+        let i = (player * 3 + size) as usize;
+        if count < 0 || count + self.b.layers[i].count_ones() as i32 > 2 {
+            return Err(SetupError::TooMany);
+        }
+        self.b.pieces[i] = count;
+        return Ok(());
+    }
+
+    pub fn set_player(&mut self, player: i32) -> Result<(), SetupError> {
+        if !(0..2).contains(&player) {
+            return Err(SetupError::OffBoard);
+        }
+        self.b.player = player;
+        return Ok(());
+    }
+
+    pub fn clear(&mut self) {
+        self.b = Board {
+            player: self.b.player,
+            ..Board::new()
+        };
+    }
+
+    pub fn finish(&self, auto_select: bool) -> Result<GameBoard, SetupError> {
+        return GameBoard::from_position(self.b, auto_select);
+    }
+}
+
+impl Default for Setup {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl GameBoard {
+    // Game starting from `b`, history and plies count from there
+    pub fn from_position(b: Board, auto_select: bool) -> Result<GameBoard, SetupError> {
+        check_board(&b)?;
+        if b.get_state() != 0 {
+            return Err(SetupError::GameOver);
+        }
+        let mut g = GameBoard::new(auto_select);
+        g.start = b;
+        g.b = b;
+        return Ok(g);
+    }
+
+    pub fn get_start(&self) -> &Board {
+        return &self.start;
+    }
+}
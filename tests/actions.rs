@@ -0,0 +1,126 @@
+use gobblers::{
+    actions::{Action, Ending, TakebackPolicy},
+    metadata::GameResult,
+    Branching, GameBoard, State,
+};
+
+mod common;
+
+use common::new_move;
+
+fn play(g: &mut GameBoard, plies: usize) {
+    for _ in 0..plies {
+        let m = g.get_moves()[0];
+        assert!(g.do_move(m));
+    }
+}
+
+#[test]
+fn resign_ends_the_game() {
+    let mut g = GameBoard::new(false);
+    play(&mut g, 1);
+    assert!(g.resign(0));
+    assert_eq!(g.get_ending(), Some(Ending::Resign(0)));
+    assert_eq!(g.get_state(), State::Win);
+    assert!(!g.do_move(g.get_moves()[0]));
+    assert!(!g.resign(1));
+}
+
+#[test]
+fn draw_by_agreement() {
+    let mut g = GameBoard::new(false);
+    assert!(g.offer_draw(0));
+    assert!(!g.accept_draw(0));
+    assert!(g.accept_draw(1));
+    assert_eq!(g.get_ending(), Some(Ending::Agreement));
+    assert_eq!(g.get_state(), State::Draw);
+}
+
+#[test]
+fn draw_offer_is_declined_or_lapses() {
+    let mut g = GameBoard::new(false);
+    assert!(g.offer_draw(0));
+    assert!(g.decline_draw(1));
+    assert_eq!(g.get_draw_offer(), None);
+    play(&mut g, 1);
+    assert!(g.offer_draw(0));
+    play(&mut g, 1);
+    assert_eq!(g.get_draw_offer(), None);
+    assert!(!g.accept_draw(1));
+    assert_eq!(g.get_state(), State::InGame);
+}
+
+#[test]
+fn takeback_on_request() {
+    let mut g = GameBoard::new(false);
+    play(&mut g, 3);
+    let before = g.get_history()[..1].to_vec();
+    // Player 0 moved last but player 1 replied already
+    assert!(g.request_takeback(1));
+    assert!(g.accept_takeback(0));
+    assert_eq!(g.get_history(), &before);
+    assert_eq!(g.player(), 1);
+    // Player 1 has no move left to take back
+    assert!(!g.request_takeback(1));
+    assert!(g.request_takeback(0));
+    assert!(g.decline_takeback(1));
+    assert_eq!(g.ply(), 1);
+    let actions: Vec<Action> = g.get_events().iter().map(|e| e.action).collect();
+    assert_eq!(
+        actions,
+        [
+            Action::RequestTakeback,
+            Action::AcceptTakeback,
+            Action::RequestTakeback,
+            Action::DeclineTakeback
+        ]
+    );
+}
+
+#[test]
+fn takeback_removes_the_moves_from_the_record() {
+    let mut g = GameBoard::new(false);
+    g.branching = Branching::Variation;
+    for (size, to) in [(2, 0), (2, 3), (2, 1), (2, 4)] {
+        assert!(g.do_move(new_move(size, to)));
+    }
+    let kept = g.get_history()[..2].to_vec();
+    assert!(g.request_takeback(0));
+    assert!(g.accept_takeback(1));
+    assert_eq!(g.get_main_line(), kept);
+    assert!(!g.redo_move());
+
+    // The game goes on in the main line and gets its result there
+    for (size, to) in [(2, 1), (0, 8), (1, 2)] {
+        assert!(g.do_move(new_move(size, to)));
+    }
+    assert_eq!(g.get_state(), State::Loss);
+    assert!(g.is_main_line());
+    assert_eq!(&g.get_main_line(), g.get_history());
+    assert_eq!(g.get_result(), GameResult::FirstWins);
+}
+
+#[test]
+fn takeback_policy() {
+    let mut g = GameBoard::new(false);
+    play(&mut g, 2);
+    g.takeback_policy = TakebackPolicy::Never;
+    assert!(!g.request_takeback(1));
+    g.takeback_policy = TakebackPolicy::Always;
+    assert!(g.request_takeback(1));
+    assert_eq!(g.ply(), 1);
+    assert!(!g.request_takeback(1));
+}
+
+#[test]
+fn actions_are_saved_with_the_game() {
+    let mut g = GameBoard::new(false);
+    play(&mut g, 2);
+    g.offer_draw(1);
+    let loaded = GameBoard::from_record(g.to_record(), false).unwrap();
+    assert_eq!(loaded.get_draw_offer(), Some(1));
+    assert_eq!(loaded.get_events(), g.get_events());
+    g.resign(1);
+    let loaded = GameBoard::from_record(g.to_record(), false).unwrap();
+    assert_eq!(loaded.get_state(), State::Win);
+}
@@ -0,0 +1,76 @@
+use gobblers::{
+    advisor::Warning,
+    search::{EvalKind, Search},
+    GameBoard, SelectionEvent,
+};
+
+mod common;
+
+use common::new_move;
+
+// Player 0 has big pieces on 0 and 1 and is to move
+fn threat() -> GameBoard {
+    let mut g = GameBoard::new(false);
+    g.set_advisor(Search::empty(), 6);
+    for m in [
+        new_move(2, 0),
+        new_move(0, 8),
+        new_move(2, 1),
+        new_move(0, 7),
+    ] {
+        assert!(g.do_move(m));
+    }
+    return g;
+}
+
+#[test]
+fn hint_finds_the_win() {
+    let g = threat();
+    let hint = g.hint().unwrap();
+    assert_eq!(hint.kind, EvalKind::Win);
+    assert_eq!(hint.plies, 1);
+    assert_eq!(hint.m.to, 2);
+}
+
+#[test]
+fn warns_about_a_missed_win() {
+    let mut g = threat();
+    g.select_new(1);
+    assert!(matches!(g.select_board(5), SelectionEvent::Dropped(_)));
+    assert!(matches!(g.check_select(), Some(Warning::MissesWin { best }) if best.m.to == 2));
+    g.select_new(1);
+    g.select_board(2);
+    assert_eq!(g.check_select(), None);
+}
+
+#[test]
+fn warns_about_walking_into_a_loss() {
+    let mut g = GameBoard::new(false);
+    g.set_advisor(Search::empty(), 6);
+    for m in [
+        new_move(0, 0),
+        new_move(1, 0),
+        new_move(0, 3),
+        new_move(0, 7),
+        new_move(2, 6),
+    ] {
+        assert!(g.do_move(m));
+    }
+    // Player 1 has to keep the piece of player 0 on 0 covered
+    g.select_board(0);
+    g.select_board(1);
+    assert!(matches!(
+        g.check_select(),
+        Some(Warning::WalksIntoLoss { plies: 1, best }) if best.m == new_move(1, 3)
+    ));
+    g.select_new(1);
+    g.select_board(3);
+    assert_eq!(g.check_select(), None);
+}
+
+#[test]
+fn no_advice_without_advisor() {
+    let mut g = threat();
+    g.remove_advisor();
+    assert_eq!(g.hint(), None);
+}
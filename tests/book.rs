@@ -0,0 +1,67 @@
+use std::fs;
+
+use gobblers::search::{book::Book, EvalKind, Evaluation};
+
+mod common;
+
+use common::temp_path;
+
+fn eval(kind: EvalKind, depth: u16) -> Evaluation {
+    return Evaluation {
+        kind,
+        depth,
+        time: 0.5,
+        nodes: 1234,
+    };
+}
+
+#[test]
+fn written_book_reads_back() {
+    let path = temp_path("roundtrip.book");
+    let entries = [
+        (42, eval(EvalKind::Win, 3)),
+        (7, eval(EvalKind::Loss, 8)),
+        (1 << 50, eval(EvalKind::Draw, 10)),
+        (99, eval(EvalKind::TooFar, 6)),
+    ];
+    Book::write(&path, entries).unwrap();
+    let book = Book::open(&path).unwrap();
+    assert_eq!(book.len(), entries.len());
+    for (id, e) in entries {
+        assert_eq!(book.get(id), Some(e));
+    }
+    assert_eq!(book.get(8), None);
+    let ids: Vec<u64> = book.iter().map(|(id, _)| id).collect();
+    assert_eq!(ids, [7, 42, 99, 1 << 50]);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn unknown_kind_is_a_miss() {
+    let path = temp_path("corrupt.book");
+    Book::write(
+        &path,
+        [(1, eval(EvalKind::Win, 1)), (2, eval(EvalKind::Draw, 2))],
+    )
+    .unwrap();
+    let mut bytes = fs::read(&path).unwrap();
+    // The kind byte follows the header and the id
+    bytes[16 + 8] = 9;
+    fs::write(&path, bytes).unwrap();
+    let book = Book::open(&path).unwrap();
+    assert_eq!(book.get(1), None);
+    assert_eq!(book.get(2), Some(eval(EvalKind::Draw, 2)));
+    assert_eq!(book.iter().count(), 1);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn huge_length_is_rejected() {
+    let path = temp_path("huge.book");
+    Book::write(&path, [(1, eval(EvalKind::Win, 1))]).unwrap();
+    let mut bytes = fs::read(&path).unwrap();
+    bytes[8..16].copy_from_slice(&u64::MAX.to_le_bytes());
+    fs::write(&path, bytes).unwrap();
+    assert!(Book::open(&path).is_err());
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,51 @@
+use gobblers::{
+    bot::{Bot, Difficulty},
+    rng::Rng,
+    search::{EvalKind, Search},
+    GameBoard, Move, State,
+};
+
+// The moves a bot picks playing against itself
+fn game(seed: u64) -> Vec<Move> {
+    let mut bot = Bot::new(Search::empty(), Difficulty::Easy, seed);
+    let mut g = GameBoard::new(false);
+    while g.get_state() == State::InGame && g.ply() < 20 {
+        let m = bot.choose(&g).unwrap();
+        assert!(g.do_move(m));
+    }
+    return g.get_history().to_vec();
+}
+
+#[test]
+fn same_seed_same_choices() {
+    assert_eq!(game(3), game(3));
+}
+
+#[test]
+fn beginner_avoids_losing_moves() {
+    let mut rng = Rng::new(29);
+    let mut bot = Bot::new(Search::empty(), Difficulty::Beginner, 1);
+    let mut checked = 0;
+    while checked < 50 {
+        let mut g = GameBoard::new(false);
+        for _ in 0..2 + rng.below(6) {
+            let moves = g.get_moves();
+            if moves.is_empty() {
+                break;
+            }
+            g.do_move(moves[rng.below(moves.len())]);
+        }
+        let candidates = bot.candidates(&g);
+        if !candidates.iter().any(|c| c.kind == EvalKind::Loss)
+            || candidates.iter().all(|c| c.kind == EvalKind::Loss)
+        {
+            continue;
+        }
+        checked += 1;
+        for _ in 0..10 {
+            let m = bot.choose(&g).unwrap();
+            let chosen = candidates.iter().find(|c| c.m == m).unwrap();
+            assert_ne!(chosen.kind, EvalKind::Loss, "{:?}", g.get_history());
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::{collections::HashMap, fs};
+
+use gobblers::{
+    search::{
+        cache::{Cache, CacheStats, ENTRY_BYTES},
+        load_scorebook, save_scorebook, EvalKind, Evaluation, Search,
+    },
+    GameBoard,
+};
+
+mod common;
+
+use common::temp_path;
+
+fn eval(kind: EvalKind, depth: u16) -> Evaluation {
+    return Evaluation {
+        kind,
+        depth,
+        time: 0.0,
+        nodes: 0,
+    };
+}
+
+// Ids sharing a shard with id 0: with one entry per shard, a shallow entry
+// next to a deep result is evicted only if they share the shard
+fn same_shard(count: usize) -> Vec<u64> {
+    let mut ids = vec![0];
+    let mut id = 0;
+    while ids.len() < count {
+        id += 1;
+        let cache = Cache::new(HashMap::new());
+        cache.set_memory_limit(Some(1));
+        cache.insert(0, eval(EvalKind::Win, 9));
+        cache.insert(id, eval(EvalKind::TooFar, 0));
+        if cache.get(id).is_none() {
+            ids.push(id);
+        }
+    }
+    return ids;
+}
+
+#[test]
+fn eviction_keeps_decisive_and_deep_entries() {
+    let [a, b, c, d, e, f] = same_shard(6)[..] else {
+        unreachable!();
+    };
+    let cache = Cache::new(HashMap::new());
+    // Three entries per shard
+    cache.set_memory_limit(Some(3 * 64 * ENTRY_BYTES));
+    cache.insert(a, eval(EvalKind::TooFar, 2));
+    cache.insert(b, eval(EvalKind::Win, 1));
+    cache.insert(c, eval(EvalKind::Loss, 5));
+    cache.insert(d, eval(EvalKind::TooFar, 8));
+    assert_eq!(cache.get(a), None);
+    cache.insert(e, eval(EvalKind::Draw, 3));
+    assert_eq!(cache.get(d), None);
+    // Only decisive entries are left, the shallowest goes
+    cache.insert(f, eval(EvalKind::Win, 0));
+    assert_eq!(cache.get(f), None);
+    for id in [b, c, e] {
+        assert!(cache.get(id).is_some());
+    }
+
+    assert!(cache.begin(c, |_| true).is_some());
+    assert!(cache.begin(a, |_| true).is_none());
+    cache.finish(a, None);
+    assert_eq!(
+        cache.stats(),
+        CacheStats {
+            entries: 3,
+            capacity: 3 * 64,
+            hits: 1,
+            misses: 1,
+            evictions: 3,
+            evicted_decisive: 1,
+        }
+    );
+}
+
+#[test]
+fn flush_keeps_entries_evicted_from_memory() {
+    let path = temp_path("evicted.scorebook");
+    let stored: HashMap<u64, Evaluation> = (0..500)
+        .map(|id| (id, eval(EvalKind::Win, (id % 7) as u16)))
+        .collect();
+    save_scorebook(&path, &stored).unwrap();
+
+    let mut search = Search::open(&path);
+    search.set_memory_limit(Some(64 * ENTRY_BYTES));
+    search.evaluate(&GameBoard::new(false), 2);
+    assert!(search.cache_stats().evicted_decisive > 0);
+    search.flush();
+
+    let entries = load_scorebook(&path).unwrap();
+    for (id, e) in &stored {
+        assert_eq!(entries.get(id), Some(e));
+    }
+    fs::remove_file(path).unwrap();
+}
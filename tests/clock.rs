@@ -0,0 +1,138 @@
+use std::{sync::Arc, time::Duration};
+
+use gobblers::{
+    clock::{ManualClock, TimeControl},
+    GameBoard, State,
+};
+
+fn secs(s: u64) -> Duration {
+    return Duration::from_secs(s);
+}
+
+fn game(control: TimeControl) -> (GameBoard, Arc<ManualClock>) {
+    let source = Arc::new(ManualClock::new());
+    let mut g = GameBoard::new(false);
+    g.set_clock(control, source.clone());
+    g.start_clock();
+    return (g, source);
+}
+
+fn play(g: &mut GameBoard) {
+    let m = g.get_moves()[0];
+    assert!(g.do_move(m));
+}
+
+#[test]
+fn increment_is_added_after_a_move() {
+    let (mut g, source) = game(TimeControl::new(secs(60), secs(2), secs(0)));
+    source.advance(secs(5));
+    play(&mut g);
+    source.advance(secs(3));
+    let clock = g.get_clock().unwrap();
+    assert_eq!(clock.remaining(0), secs(57));
+    assert_eq!(clock.remaining(1), secs(57));
+    assert_eq!(clock.turn(), 1);
+}
+
+#[test]
+fn delay_is_not_charged() {
+    let (mut g, source) = game(TimeControl::new(secs(60), secs(0), secs(3)));
+    source.advance(secs(2));
+    play(&mut g);
+    source.advance(secs(5));
+    let clock = g.get_clock().unwrap();
+    assert_eq!(clock.remaining(0), secs(60));
+    assert_eq!(clock.remaining(1), secs(58));
+}
+
+#[test]
+fn delay_is_granted_once_per_turn() {
+    let (mut g, source) = game(TimeControl::new(secs(60), secs(0), secs(3)));
+    source.advance(secs(2));
+    g.pause_clock();
+    g.start_clock();
+    source.advance(secs(2));
+    assert_eq!(g.get_clock().unwrap().remaining(0), secs(59));
+    let other = Arc::new(ManualClock::new());
+    g.set_clock_source(other.clone());
+    other.advance(secs(2));
+    assert_eq!(g.get_clock().unwrap().remaining(0), secs(57));
+    // The next turn gets its own delay
+    play(&mut g);
+    other.advance(secs(4));
+    assert_eq!(g.get_clock().unwrap().remaining(1), secs(59));
+}
+
+#[test]
+fn paused_clock_does_not_run() {
+    let (mut g, source) = game(TimeControl::new(secs(60), secs(0), secs(0)));
+    source.advance(secs(10));
+    g.pause_clock();
+    source.advance(secs(100));
+    assert_eq!(g.get_clock().unwrap().remaining(0), secs(50));
+    assert!(!g.check_clock());
+    g.start_clock();
+    source.advance(secs(10));
+    assert_eq!(g.get_clock().unwrap().remaining(0), secs(40));
+}
+
+#[test]
+fn flag_ends_the_game() {
+    let (mut g, source) = game(TimeControl::new(secs(10), secs(0), secs(0)));
+    play(&mut g);
+    source.advance(secs(11));
+    assert!(g.check_clock());
+    assert_eq!(g.get_clock().unwrap().flagged(), Some(1));
+    assert_eq!(g.get_state(), State::Loss);
+    assert!(!g.do_move(g.get_moves()[0]));
+    g.undo_move();
+    assert_eq!(g.get_state(), State::Win);
+}
+
+#[test]
+fn new_clock_resumes_a_timed_out_game() {
+    let (mut g, source) = game(TimeControl::new(secs(10), secs(0), secs(0)));
+    source.advance(secs(11));
+    assert!(g.check_clock());
+    assert_eq!(g.get_state(), State::Loss);
+    g.set_clock(TimeControl::new(secs(10), secs(0), secs(0)), source);
+    assert_eq!(g.get_state(), State::InGame);
+    assert!(g.do_move(g.get_moves()[0]));
+}
+
+#[test]
+fn move_after_time_is_up_is_refused() {
+    let (mut g, source) = game(TimeControl::new(secs(10), secs(0), secs(0)));
+    source.advance(secs(10));
+    assert!(!g.do_move(g.get_moves()[0]));
+    assert_eq!(g.get_clock().unwrap().flagged(), Some(0));
+    assert!(g.get_history().is_empty());
+}
+
+#[test]
+fn clock_is_saved_with_the_game() {
+    let (mut g, source) = game(TimeControl::new(secs(60), secs(1), secs(0)));
+    source.advance(secs(4));
+    play(&mut g);
+    source.advance(secs(6));
+    let record = g.to_record();
+    let mut loaded = GameBoard::from_record(record, false).unwrap();
+    loaded.set_clock_source(source.clone());
+    let clock = loaded.get_clock().unwrap();
+    assert!(!clock.is_running());
+    assert_eq!(clock.remaining(0), secs(57));
+    assert_eq!(clock.remaining(1), secs(54));
+    loaded.start_clock();
+    source.advance(secs(4));
+    assert_eq!(loaded.get_clock().unwrap().remaining(1), secs(50));
+}
+
+#[test]
+fn out_of_range_values_do_not_panic() {
+    let (mut g, _) = game(TimeControl::new(secs(60), Duration::MAX, secs(0)));
+    let clock = g.get_clock().unwrap();
+    assert_eq!(clock.remaining(-1), Duration::ZERO);
+    assert_eq!(clock.remaining(2), Duration::ZERO);
+    play(&mut g);
+    assert_eq!(g.get_clock().unwrap().remaining(0), Duration::MAX);
+}
@@ -0,0 +1,21 @@
+// Shared by the integration tests, each test only uses some of it
+#![allow(dead_code)]
+
+use std::{env, path::PathBuf, process};
+
+use gobblers::Move;
+
+// A new piece of `size` placed on `to`
+pub fn new_move(size: i32, to: i32) -> Move {
+    return Move {
+        is_new: true,
+        size,
+        from: -1,
+        to,
+    };
+}
+
+// File in the temp dir private to this test process
+pub fn temp_path(name: &str) -> PathBuf {
+    return env::temp_dir().join(format!("gobblers-{}-{}", process::id(), name));
+}
@@ -0,0 +1,84 @@
+use std::sync::{Arc, Mutex};
+
+use gobblers::{events::GameEvent, GameBoard, Select, State};
+
+mod common;
+
+use common::new_move;
+
+fn observed(auto_select: bool) -> (GameBoard, Arc<Mutex<Vec<GameEvent>>>) {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut g = GameBoard::new(auto_select);
+    let log = events.clone();
+    g.set_observer(move |e| log.lock().unwrap().push(*e));
+    return (g, events);
+}
+
+fn take(events: &Mutex<Vec<GameEvent>>) -> Vec<GameEvent> {
+    return std::mem::take(&mut *events.lock().unwrap());
+}
+
+#[test]
+fn selection_and_moves() {
+    let (mut g, events) = observed(true);
+    g.select_new(1);
+    let picked = g.get_select().1;
+    assert_eq!(
+        take(&events),
+        [GameEvent::SelectionChanged(Select::From, picked)]
+    );
+    // Rejected clicks change nothing
+    g.select_new(5);
+    assert_eq!(take(&events), []);
+    g.select_board(4);
+    assert_eq!(
+        take(&events),
+        [
+            GameEvent::MoveMade(new_move(1, 4)),
+            GameEvent::SelectionChanged(Select::None, g.get_select().1)
+        ]
+    );
+    g.undo_move();
+    assert_eq!(take(&events), [GameEvent::MoveUndone(new_move(1, 4))]);
+    g.redo_move();
+    assert_eq!(take(&events), [GameEvent::MoveMade(new_move(1, 4))]);
+}
+
+#[test]
+fn game_over() {
+    let (mut g, events) = observed(false);
+    for m in [
+        new_move(0, 0),
+        new_move(0, 3),
+        new_move(0, 1),
+        new_move(0, 4),
+    ] {
+        g.do_move(m);
+    }
+    take(&events);
+    g.do_move(new_move(1, 2));
+    assert_eq!(
+        take(&events),
+        [
+            GameEvent::MoveMade(new_move(1, 2)),
+            GameEvent::GameOver(State::Loss)
+        ]
+    );
+    g.undo_move();
+    g.resign(0);
+    assert_eq!(
+        take(&events),
+        [
+            GameEvent::MoveUndone(new_move(1, 2)),
+            GameEvent::GameOver(State::Loss)
+        ]
+    );
+}
+
+#[test]
+fn clones_are_quiet() {
+    let (g, events) = observed(false);
+    let mut copy = g.clone();
+    copy.do_move(new_move(0, 0));
+    assert_eq!(take(&events), []);
+}
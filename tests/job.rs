@@ -0,0 +1,67 @@
+use std::{
+    fs,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use gobblers::search::{job::Job, Report, Search};
+
+mod common;
+
+use common::temp_path;
+
+// Search logging the ids it evaluates for a job
+fn logged(stop: Option<Arc<AtomicBool>>) -> (Search, Arc<Mutex<Vec<u64>>>) {
+    let ids = Arc::new(Mutex::new(Vec::new()));
+    let log = ids.clone();
+    let mut search = Search::empty();
+    search.set_progress(move |r| match r {
+        Report::Evaluated { id, .. } => log.lock().unwrap().push(*id),
+        Report::Checkpoint { .. } => {
+            if let Some(stop) = &stop {
+                stop.store(true, Ordering::Relaxed);
+            }
+        }
+        _ => (),
+    });
+    return (search, ids);
+}
+
+#[test]
+fn interrupted_job_resumes_where_it_stopped() {
+    let path = temp_path("resume.job");
+    let stop = Arc::new(AtomicBool::new(false));
+    let (search, first) = logged(Some(stop.clone()));
+    let mut job = Job::new(&search, 3, 1);
+    let total = job.total();
+    job.save(&path).unwrap();
+    job.run(&search, Some(&path), Some(&stop)).unwrap();
+    assert!(!job.is_finished());
+    let first = first.lock().unwrap().clone();
+    assert_eq!(first.len(), job.done());
+
+    // A crash while appending leaves part of a record behind
+    let mut journal = fs::read(Job::journal(&path)).unwrap();
+    journal.extend([7; 5]);
+    fs::write(Job::journal(&path), journal).unwrap();
+
+    // A new process knows nothing but the checkpoint
+    let mut loaded = Job::load(&path).unwrap();
+    assert_eq!(loaded, job);
+    let (search, second) = logged(None);
+    loaded.run(&search, Some(&path), None).unwrap();
+    assert!(loaded.is_finished());
+    let second = second.lock().unwrap().clone();
+    assert_eq!(second.len(), total - first.len());
+
+    let mut ids = [first, second].concat();
+    ids.sort_unstable();
+    ids.dedup();
+    assert_eq!(ids.len(), total);
+    // The first run's evaluations came back from the journal
+    assert_eq!(search.cache_stats().entries, total);
+    fs::remove_file(Job::journal(&path)).unwrap();
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,49 @@
+use gobblers::{
+    mcts::{Mcts, MctsConfig},
+    GameBoard,
+};
+
+mod common;
+
+use common::new_move;
+
+fn config(seed: u64) -> MctsConfig {
+    return MctsConfig {
+        playouts: 2000,
+        seed,
+        ..MctsConfig::default()
+    };
+}
+
+// Player 0 has big pieces on 0 and 1 and is to move
+fn mate_in_one() -> GameBoard {
+    let mut g = GameBoard::new(false);
+    for m in [
+        new_move(2, 0),
+        new_move(0, 4),
+        new_move(2, 1),
+        new_move(0, 8),
+    ] {
+        assert!(g.do_move(m));
+    }
+    return g;
+}
+
+#[test]
+fn finds_mate_in_one() {
+    let g = mate_in_one();
+    for seed in 0..4 {
+        let m = Mcts::new(config(seed)).best_move(&g).unwrap();
+        assert!(m.is_new && m.to == 2, "{:?}", m);
+    }
+}
+
+#[test]
+fn same_seed_same_analysis() {
+    let mut g = GameBoard::new(false);
+    g.do_move(new_move(1, 4));
+    let first = Mcts::new(config(5)).analyse(&g);
+    assert_eq!(Mcts::new(config(5)).analyse(&g), first);
+    let mut mcts = Mcts::new(config(5));
+    assert_eq!(mcts.best_move(&g), Some(first[0].m));
+}
@@ -0,0 +1,110 @@
+use gobblers::{
+    bot::Difficulty,
+    metadata::{EngineSettings, GameResult, Metadata, PlayerInfo, Termination},
+    Branching, GameBoard,
+};
+
+mod common;
+
+use common::new_move;
+
+// Player 0 completes the top row on the last move
+fn won() -> GameBoard {
+    let mut g = GameBoard::new(false);
+    for m in [
+        new_move(0, 0),
+        new_move(0, 3),
+        new_move(0, 1),
+        new_move(0, 4),
+        new_move(1, 2),
+    ] {
+        assert!(g.do_move(m));
+    }
+    return g;
+}
+
+#[test]
+fn result_follows_the_main_line() {
+    let mut g = won();
+    assert_eq!(g.get_result(), GameResult::FirstWins);
+    assert_eq!(g.get_result().to_string(), "1-0");
+    assert_eq!(g.get_metadata().termination, Some(Termination::Line));
+    g.goto_first();
+    assert_eq!(g.get_result(), GameResult::FirstWins);
+    // A variation that ends differently is only analysis
+    g.branching = Branching::Variation;
+    g.goto_ply(4);
+    g.do_move(new_move(1, 5));
+    assert_eq!(g.get_result(), GameResult::FirstWins);
+    // Without the winning move the game goes on in the variation
+    g.goto_main_line();
+    g.goto_ply(5);
+    g.delete_variation();
+    g.goto_last();
+    assert_eq!(g.get_line()[4], new_move(1, 5));
+    assert_eq!(g.get_result(), GameResult::Ongoing);
+    assert_eq!(g.get_metadata().termination, None);
+}
+
+#[test]
+fn game_finished_in_a_variation() {
+    let mut g = GameBoard::new(false);
+    g.branching = Branching::Variation;
+    for m in [
+        new_move(0, 0),
+        new_move(0, 3),
+        new_move(0, 1),
+        new_move(0, 4),
+    ] {
+        assert!(g.do_move(m));
+    }
+    g.undo_move();
+    g.undo_move();
+    // Played on instead of the main line, which is still unfinished
+    for m in [new_move(1, 1), new_move(0, 4), new_move(0, 2)] {
+        assert!(g.do_move(m));
+    }
+    assert!(!g.is_main_line());
+    assert_eq!(g.get_result(), GameResult::FirstWins);
+    assert_eq!(g.get_metadata().termination, Some(Termination::Line));
+    // Analysis after the game doesn't change the result
+    g.goto_ply(4);
+    assert!(g.do_move(new_move(2, 8)));
+    assert!(g.do_move(new_move(1, 2)));
+    assert_eq!(g.get_result(), GameResult::FirstWins);
+}
+
+#[test]
+fn endings() {
+    let mut g = GameBoard::new(false);
+    g.do_move(new_move(0, 0));
+    g.resign(0);
+    assert_eq!(g.get_result().to_string(), "0-1");
+    assert_eq!(g.get_metadata().termination, Some(Termination::Resignation));
+    let mut g = GameBoard::new(false);
+    g.offer_draw(0);
+    g.accept_draw(1);
+    assert_eq!(g.get_result().to_string(), "½-½");
+    assert_eq!(g.get_metadata().termination, Some(Termination::Agreement));
+}
+
+#[test]
+fn metadata_is_saved_with_the_game() {
+    let mut g = won();
+    let mut metadata = Metadata::default();
+    metadata.players[0].name = "Alice".to_string();
+    metadata.players[1] = PlayerInfo {
+        name: "Bot".to_string(),
+        id: Some("bot-1".to_string()),
+        engine: Some(EngineSettings {
+            difficulty: Some(Difficulty::Hard),
+            max_depth: 7,
+        }),
+    };
+    metadata.start_time = Some(1_700_000_000);
+    g.set_metadata(metadata);
+    assert_eq!(g.get_result(), GameResult::FirstWins);
+    let loaded = GameBoard::from_record(g.to_record(), false).unwrap();
+    assert_eq!(loaded.get_metadata(), g.get_metadata());
+    assert_eq!(loaded.get_metadata().variant, "standard");
+}
@@ -0,0 +1,93 @@
+use gobblers::{Branching, GameBoard};
+
+mod common;
+
+use common::new_move;
+
+// Five opening moves, none of them ends the game
+fn played() -> GameBoard {
+    let mut g = GameBoard::new(false);
+    for m in [
+        new_move(2, 4),
+        new_move(0, 0),
+        new_move(1, 8),
+        new_move(0, 2),
+        new_move(2, 6),
+    ] {
+        assert!(g.do_move(m));
+    }
+    return g;
+}
+
+#[test]
+fn undo_and_redo() {
+    let mut g = played();
+    let end = *g.get_board();
+    let line = g.get_line();
+    assert!(g.undo_move());
+    assert!(g.undo_move());
+    assert_eq!(g.ply(), 3);
+    assert_eq!(g.get_history(), &line[..3]);
+    assert_eq!(g.line_len(), 5);
+    assert!(g.redo_move());
+    assert!(g.redo_move());
+    assert!(!g.redo_move());
+    assert_eq!(g.get_board(), &end);
+    g.goto_first();
+    assert!(!g.undo_move());
+    assert_eq!(g.get_board(), g.get_start());
+}
+
+#[test]
+fn goto_ply() {
+    let mut g = played();
+    let line = g.get_line();
+    let mut boards = Vec::new();
+    for ply in 0..=5 {
+        assert!(g.goto_ply(ply));
+        boards.push(*g.get_board());
+        assert_eq!(g.get_history(), &line[..ply]);
+    }
+    for ply in [3, 0, 5, 1, 4] {
+        assert!(g.goto_ply(ply));
+        assert_eq!(g.get_board(), &boards[ply]);
+    }
+    assert!(!g.goto_ply(6));
+    assert_eq!(g.ply(), 4);
+    assert_eq!(g.get_line(), line);
+}
+
+#[test]
+fn truncate_drops_the_rest_of_the_line() {
+    let mut g = played();
+    g.goto_ply(2);
+    // The next move of the line just steps forward
+    assert!(g.do_move(new_move(1, 8)));
+    assert_eq!(g.line_len(), 5);
+    g.undo_move();
+    assert!(g.do_move(new_move(0, 8)));
+    assert_eq!(g.ply(), 3);
+    assert_eq!(g.line_len(), 3);
+    assert!(!g.redo_move());
+    g.undo_move();
+    assert_eq!(g.get_variations(), [new_move(0, 8)]);
+}
+
+#[test]
+fn variation_keeps_the_main_line() {
+    let mut g = played();
+    let main = g.get_line();
+    g.branching = Branching::Variation;
+    g.goto_ply(2);
+    assert!(g.do_move(new_move(0, 8)));
+    assert_eq!(g.line_len(), 3);
+    assert!(!g.is_main_line());
+    assert_eq!(g.get_main_line(), main);
+    g.undo_move();
+    assert_eq!(g.get_variations(), [new_move(1, 8), new_move(0, 8)]);
+    g.goto_main_line();
+    assert_eq!(g.ply(), 2);
+    assert_eq!(g.get_line(), main);
+    g.goto_last();
+    assert_eq!(g.get_history(), &main[..]);
+}
@@ -0,0 +1,79 @@
+use std::collections::HashSet;
+
+use gobblers::{
+    perft::{divide, perft},
+    rng::Rng,
+    search::search_moves,
+    Board, GameBoard, Move, State,
+};
+
+// Counted with a separate stack-per-cell implementation
+const START: [u64; 6] = [1, 27, 675, 20313, 572472, 16635384];
+
+#[test]
+fn perft_from_start() {
+    let b = Board::new();
+    for (depth, &nodes) in START.iter().enumerate().take(5) {
+        assert_eq!(perft(&b, depth as i32), nodes, "depth {}", depth);
+    }
+}
+
+#[test]
+#[ignore = "slow in debug builds"]
+fn perft_from_start_deep() {
+    assert_eq!(perft(&Board::new(), 5), START[5]);
+}
+
+#[test]
+fn divide_sums_to_perft() {
+    let b = Board::new();
+    let split = divide(&b, 3);
+    assert_eq!(split.len(), 27);
+    assert_eq!(split.iter().map(|s| s.1).sum::<u64>(), START[3]);
+    for (m, nodes) in split {
+        let mut next = b;
+        next.do_move(m);
+        assert_eq!(perft(&next, 2), nodes, "{:?}", m);
+    }
+}
+
+fn random_positions(seed: u64, count: usize) -> Vec<Board> {
+    let mut rng = Rng::new(seed);
+    let mut positions = Vec::new();
+    while positions.len() < count {
+        let mut b = GameBoard::new(false);
+        for _ in 0..rng.below(40) {
+            let moves = b.get_moves();
+            if b.get_state() != State::InGame || moves.is_empty() {
+                break;
+            }
+            b.do_move(moves[rng.below(moves.len())]);
+        }
+        positions.push(*b.get_board());
+    }
+    return positions;
+}
+
+#[test]
+fn search_moves_match_get_moves() {
+    for b in random_positions(1, 2000) {
+        let searched: HashSet<Move> = search_moves(&b).into_iter().collect();
+        let generated: Vec<Move> = b.get_moves();
+        let unique: HashSet<Move> = generated.iter().copied().collect();
+        assert_eq!(unique.len(), generated.len(), "duplicates in {:?}", b);
+        assert_eq!(searched, unique, "{:?}", b);
+    }
+}
+
+#[test]
+fn get_moves_are_valid() {
+    for b in random_positions(2, 500) {
+        for m in b.get_moves() {
+            let from_ok = match m.is_new {
+                true => b.is_left(m.size),
+                false => b.is_movable(m.size, m.from),
+            };
+            assert!(from_ok && b.is_free(m.size, m.to), "{:?} in {:?}", m, b);
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use gobblers::{search::Search, GameBoard};
+
+#[test]
+fn finished_ponder_fills_the_cache() {
+    let search = Search::empty();
+    let g = GameBoard::new(false);
+    let mut ponder = search.ponder(&g, 2, None);
+    while !ponder.is_finished() {
+        thread::sleep(Duration::from_millis(1));
+    }
+    ponder.stop();
+    // One entry per reply up to symmetry
+    assert_eq!(search.cache_stats().entries, 3 * 3);
+}
+
+#[test]
+fn stop_aborts_promptly_without_caching() {
+    let search = Search::empty();
+    let g = GameBoard::new(false);
+    let mut ponder = search.ponder(&g, 40, None);
+    thread::sleep(Duration::from_millis(100));
+    assert!(!ponder.is_finished());
+    let start = Instant::now();
+    ponder.stop();
+    assert!(start.elapsed() < Duration::from_millis(500));
+    assert!(ponder.is_finished());
+    assert_eq!(search.cache_stats().entries, 0);
+}
@@ -0,0 +1,147 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+};
+
+use gobblers::{
+    positions::Positions,
+    rng::Rng,
+    search::{load_scorebook, migrate_scorebook, save_scorebook, EvalKind, Evaluation},
+    Board, GameBoard,
+};
+
+mod common;
+
+use common::temp_path;
+
+fn eval(kind: EvalKind, depth: u16) -> Evaluation {
+    return Evaluation {
+        kind,
+        depth,
+        time: 0.0,
+        nodes: 0,
+    };
+}
+
+// Key of `b` in scorebooks from before canonical ids: layers, moves played
+// and player
+fn old_id(b: &Board, plies: u64) -> u64 {
+    return (b.get_id() >> 1) << 9 | plies << 1 | b.player as u64;
+}
+
+// Moves the pieces of `b` from cell i to cell order[i]
+fn reorder(b: &Board, order: [usize; 9]) -> Board {
+    let mut out = *b;
+    for (i, layer) in b.layers.iter().enumerate() {
+        out.layers[i] = 0;
+        for (pos, &to) in order.iter().enumerate() {
+            if layer & (1 << pos) != 0 {
+                out.layers[i] |= 1 << to;
+            }
+        }
+    }
+    return out;
+}
+
+// The 8 rotations and mirrors of `b`
+fn symmetries(b: &Board) -> Vec<Board> {
+    let rotate = [2, 5, 8, 1, 4, 7, 0, 3, 6];
+    let mirror = [6, 7, 8, 3, 4, 5, 0, 1, 2];
+    let mut all = Vec::new();
+    let mut tmp = *b;
+    for _ in 0..2 {
+        for _ in 0..4 {
+            all.push(tmp);
+            tmp = reorder(&tmp, rotate);
+        }
+        tmp = reorder(&tmp, mirror);
+    }
+    return all;
+}
+
+fn random_positions(seed: u64, count: usize) -> Vec<Board> {
+    let mut rng = Rng::new(seed);
+    let mut positions = Vec::new();
+    while positions.len() < count {
+        let mut g = GameBoard::new(false);
+        for _ in 0..rng.below(12) {
+            let moves = g.get_moves();
+            if moves.is_empty() {
+                break;
+            }
+            g.do_move(moves[rng.below(moves.len())]);
+        }
+        positions.push(*g.get_board());
+    }
+    return positions;
+}
+
+#[test]
+fn unique_position_counts() {
+    for (plies, count) in [(0, 1), (1, 10), (2, 127), (3, 1507)] {
+        let mut positions = Positions::new(&Board::new(), plies);
+        let mut ids = HashSet::new();
+        let mut last = 0;
+        for (ply, b) in positions.by_ref() {
+            assert!(ply >= last && ply <= plies);
+            last = ply;
+            // Yielded in canonical orientation, once per class
+            assert_eq!(b.get_id(), b.get_max_id());
+            assert!(ids.insert(b.get_max_id()));
+        }
+        assert_eq!(ids.len(), count);
+        assert_eq!(positions.seen(), count);
+    }
+}
+
+#[test]
+fn id_round_trip() {
+    for b in random_positions(36, 500) {
+        assert_eq!(Board::from_id(b.get_id()), b);
+    }
+}
+
+#[test]
+fn max_id_is_the_same_for_all_symmetries() {
+    for b in random_positions(37, 500) {
+        let all = symmetries(&b);
+        let max = all.iter().map(|s| s.get_id()).max().unwrap();
+        for s in all {
+            assert_eq!(s.get_max_id(), max);
+        }
+    }
+}
+
+#[test]
+fn migration_merges_symmetric_entries() {
+    let (b, mirrored) = random_positions(38, 20)
+        .into_iter()
+        .map(|b| (b, symmetries(&b)[5]))
+        .find(|(b, mirrored)| b != mirrored)
+        .unwrap();
+    let old = HashMap::from([
+        (old_id(&b, 4), eval(EvalKind::TooFar, 6)),
+        (old_id(&mirrored, 6), eval(EvalKind::Win, 3)),
+    ]);
+    let entries = migrate_scorebook(old);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[&b.get_max_id()], eval(EvalKind::Win, 3));
+}
+
+#[test]
+fn scorebooks_without_header_are_migrated_on_load() {
+    let path = temp_path("old.scorebook");
+    let b = random_positions(39, 20)[7];
+    let old = HashMap::from([(old_id(&b, 7), eval(EvalKind::Loss, 4))]);
+    fs::write(&path, bincode::serialize(&old).unwrap()).unwrap();
+    let entries = load_scorebook(&path).unwrap();
+    assert_eq!(
+        entries,
+        HashMap::from([(b.get_max_id(), eval(EvalKind::Loss, 4))])
+    );
+
+    // Saved again with the header, the canonical ids load as they are
+    save_scorebook(&path, &entries).unwrap();
+    assert_eq!(load_scorebook(&path).unwrap(), entries);
+    fs::remove_file(path).unwrap();
+}
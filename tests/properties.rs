@@ -0,0 +1,222 @@
+use gobblers::{reference::check_game_board, Board, Branching, GameBoard, Move};
+use proptest::prelude::*;
+
+const LINES: [[i32; 3]; 8] = [
+    [0, 1, 2],
+    [3, 4, 5],
+    [6, 7, 8],
+    [0, 3, 6],
+    [1, 4, 7],
+    [2, 5, 8],
+    [0, 4, 8],
+    [2, 4, 6],
+];
+
+// Owner of the biggest piece on every cell
+fn naive_tops(b: &Board) -> [Option<i32>; 9] {
+    let mut tops = [None; 9];
+    for (pos, top) in tops.iter_mut().enumerate() {
+        for size in (0..3).rev() {
+            if let Some(p) = (0..2).find(|p| b.layers[p * 3 + size] & (1 << pos) != 0) {
+                *top = Some(p as i32);
+                break;
+            }
+        }
+    }
+    return tops;
+}
+
+fn naive_view(b: &Board, player: i32) -> i32 {
+    let mut view = 0;
+    for (pos, top) in naive_tops(b).iter().enumerate() {
+        if *top == Some(player) {
+            view |= 1 << pos;
+        }
+    }
+    return view;
+}
+
+fn naive_is_line(view: i32) -> bool {
+    return LINES
+        .iter()
+        .any(|line| line.iter().all(|pos| view & (1 << pos) != 0));
+}
+
+fn naive_is_winning_spot(view: i32, pos: i32) -> bool {
+    return LINES.iter().any(|line| {
+        line.contains(&pos)
+            && line
+                .iter()
+                .all(|&other| other == pos || view & (1 << other) != 0)
+    });
+}
+
+fn check_invariants(b: &Board) {
+    for i in 0..6 {
+        assert!(b.pieces[i] >= 0, "{:?}", b);
+        assert_eq!(b.pieces[i] + b.layers[i].count_ones() as i32, 2, "{:?}", b);
+        assert_eq!(b.layers[i] & !0b111111111, 0, "{:?}", b);
+    }
+    for p in 0..2 {
+        assert_eq!(b.get_view(p), naive_view(b, p), "{:?}", b);
+    }
+    let win = naive_is_line(naive_view(b, b.player));
+    let loss = naive_is_line(naive_view(b, b.player ^ 1));
+    assert_eq!(b.get_state(), win as i32 | ((loss as i32) << 1), "{:?}", b);
+}
+
+// Plays the game picking moves by index, stops when it is over
+fn play(choices: &[usize]) -> Vec<(Board, Move)> {
+    let mut b = Board::new();
+    let mut played = Vec::new();
+    for &c in choices {
+        if b.get_state() != 0 {
+            break;
+        }
+        let moves = b.get_moves();
+        let m = moves[c % moves.len()];
+        played.push((b, m));
+        b.do_move(m);
+    }
+    return played;
+}
+
+// Random UI calls with arguments that may be off the board
+fn drive(g: &mut GameBoard, (call, a, b, c): (u8, i32, i32, i32)) {
+    match call % 12 {
+        0 => {
+            g.select_board(a);
+        }
+        1 => {
+            g.select_new(b);
+        }
+        2 => {
+            g.submit_select();
+        }
+        3 => {
+            g.remove_select();
+        }
+        4 => {
+            let m = Move {
+                is_new: a % 2 == 0,
+                size: b,
+                from: c,
+                to: a,
+            };
+            g.do_move(m);
+        }
+        5 => {
+            g.undo_move();
+        }
+        6 => {
+            g.redo_move();
+        }
+        7 => {
+            g.goto_ply(a.unsigned_abs() as usize);
+        }
+        8 => g.goto_main_line(),
+        9 => g.promote_variation(),
+        10 => {
+            g.delete_variation();
+        }
+        _ => {
+            g.get_top(a);
+            g.get_left(a, b);
+            g.is_selected_board(a);
+            g.is_selected_new(b);
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn game_board_api_never_panics(
+        auto_select in any::<bool>(),
+        variations in any::<bool>(),
+        calls in prop::collection::vec((any::<u8>(), -4..12i32, -4..12i32, -4..12i32), 0..200),
+    ) {
+        let mut g = GameBoard::new(auto_select);
+        if variations {
+            g.branching = Branching::Variation;
+        }
+        for call in calls {
+            drive(&mut g, call);
+            if let Some(what) = check_game_board(&g) {
+                panic!("{} after {:?}", what, g.get_history());
+            }
+        }
+        let loaded = GameBoard::from_record(g.to_record(), auto_select).unwrap();
+        prop_assert_eq!(loaded.to_record(), g.to_record());
+        prop_assert_eq!(loaded.get_board(), g.get_board());
+    }
+
+    #[test]
+    fn make_unmake_restores_board(choices in prop::collection::vec(any::<usize>(), 0..80)) {
+        let played = play(&choices);
+        let Some(&(last, m)) = played.last() else {
+            return Ok(());
+        };
+        let mut b = last;
+        b.do_move(m);
+        for &(before, m) in played.iter().rev() {
+            b.undo_move(m);
+            prop_assert_eq!(b, before);
+        }
+        prop_assert_eq!(b, Board::new());
+    }
+
+    #[test]
+    fn every_move_round_trips(choices in prop::collection::vec(any::<usize>(), 0..80)) {
+        for (before, _) in play(&choices) {
+            for m in before.get_moves() {
+                let mut b = before;
+                b.do_move(m);
+                check_invariants(&b);
+                prop_assert_eq!(b.player, before.player ^ 1);
+                b.undo_move(m);
+                prop_assert_eq!(b, before);
+            }
+        }
+    }
+
+    #[test]
+    fn invariants_hold_during_games(choices in prop::collection::vec(any::<usize>(), 0..80)) {
+        for (b, _) in play(&choices) {
+            check_invariants(&b);
+        }
+    }
+
+    #[test]
+    fn winning_spots_match_lines(choices in prop::collection::vec(any::<usize>(), 0..80)) {
+        for (b, _) in play(&choices) {
+            let view = naive_view(&b, b.player);
+            for pos in 0..9 {
+                prop_assert_eq!(b.is_winning_spot(pos), naive_is_winning_spot(view, pos));
+            }
+        }
+    }
+}
+
+#[test]
+fn is_line_matches_all_views() {
+    for view in 0..1 << 9 {
+        assert_eq!(Board::is_line(view), naive_is_line(view), "{:09b}", view);
+    }
+}
+
+#[test]
+fn is_winning_spot_matches_all_views() {
+    for view in 0..1 << 9 {
+        let mut b = Board::new();
+        b.layers[2] = view;
+        for pos in 0..9 {
+            assert_eq!(
+                b.is_winning_spot(pos),
+                naive_is_winning_spot(view, pos),
+                "{:09b} at {}",
+                view,
+                pos
+            );
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use gobblers::{
+    rng::Rng,
+    search::{EvalKind, Search},
+    setup::Setup,
+    GameBoard,
+};
+
+mod common;
+
+use common::new_move;
+
+#[test]
+fn prove_agrees_with_deepening() {
+    let mut rng = Rng::new(27);
+    let mut decisive = 0;
+    while decisive < 20 {
+        let mut g = GameBoard::new(false);
+        for _ in 0..2 + rng.below(8) {
+            let moves = g.get_moves();
+            if moves.is_empty() {
+                break;
+            }
+            g.do_move(moves[rng.below(moves.len())]);
+        }
+        if g.get_moves().is_empty() {
+            continue;
+        }
+        let e = Search::empty().evaluate(&g, 5);
+        if e.kind == EvalKind::TooFar {
+            continue;
+        }
+        decisive += 1;
+        let proof = Search::empty().prove(&g, 1_000_000);
+        assert_eq!(proof.kind, e.kind, "{:?}", g.get_history());
+        // Plies to the result like in the scorebook, the proof may take a
+        // longer way than the fastest one
+        if e.kind != EvalKind::Draw {
+            assert!(proof.depth >= e.depth, "{:?}", g.get_history());
+        }
+    }
+}
+
+// The proof search wanders down to 14 plies before finding the win in 3
+#[test]
+fn proof_depth_is_the_length_of_the_line() {
+    let mut g = GameBoard::new(false);
+    for m in [
+        new_move(1, 1),
+        new_move(0, 7),
+        new_move(0, 6),
+        new_move(0, 3),
+    ] {
+        assert!(g.do_move(m));
+    }
+    let proof = Search::empty().prove(&g, 1_000_000);
+    assert_eq!(proof.kind, EvalKind::Win);
+    assert_eq!(proof.depth, 3);
+}
+
+// Player 0 can only complete the top row by moving the big piece off 8,
+// which completes player 1's bottom row as well
+#[test]
+fn forced_double_line_is_a_draw() {
+    let mut s = Setup::new();
+    s.place(0, 1, 0).unwrap();
+    s.place(0, 1, 1).unwrap();
+    s.place(1, 0, 6).unwrap();
+    s.place(1, 0, 7).unwrap();
+    s.place(1, 1, 8).unwrap();
+    s.place(0, 2, 8).unwrap();
+    s.place(1, 1, 3).unwrap();
+    s.place(1, 2, 4).unwrap();
+    s.set_reserve(0, 0, 0).unwrap();
+    s.set_reserve(0, 2, 0).unwrap();
+    let g = s.finish(false).unwrap();
+    assert_eq!(Search::empty().evaluate(&g, 6).kind, EvalKind::Draw);
+    assert_eq!(Search::empty().prove(&g, 100_000).kind, EvalKind::Draw);
+}
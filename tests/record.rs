@@ -0,0 +1,146 @@
+use gobblers::{
+    record::{Node, Record, NAG_BLUNDER, NAG_GOOD},
+    Branching, GameBoard, Move,
+};
+
+mod common;
+
+use common::new_move;
+
+// Medium on 4, small on 0, and small on 8 as a variation instead
+fn branched() -> GameBoard {
+    let mut g = GameBoard::new(false);
+    g.branching = Branching::Variation;
+    g.do_move(new_move(1, 4));
+    g.do_move(new_move(0, 0));
+    g.undo_move();
+    g.do_move(new_move(0, 8));
+    return g;
+}
+
+fn board_move(from: i32, to: i32) -> Move {
+    return Move {
+        is_new: false,
+        size: 2,
+        from,
+        to,
+    };
+}
+
+#[test]
+fn promote_variation() {
+    let mut g = branched();
+    assert!(!g.is_main_line());
+    g.undo_move();
+    assert_eq!(g.get_variations(), [new_move(0, 0), new_move(0, 8)]);
+    g.redo_move();
+    g.promote_variation();
+    assert!(g.is_main_line());
+    assert_eq!(g.get_main_line(), [new_move(1, 4), new_move(0, 8)]);
+    g.undo_move();
+    assert_eq!(g.get_variations(), [new_move(0, 8), new_move(0, 0)]);
+}
+
+#[test]
+fn delete_variation() {
+    let mut g = branched();
+    assert!(g.delete_variation());
+    assert_eq!(g.ply(), 1);
+    assert_eq!(g.get_variations(), [new_move(0, 0)]);
+    assert_eq!(g.get_line(), [new_move(1, 4), new_move(0, 0)]);
+    g.goto_first();
+    assert!(!g.delete_variation());
+    g.redo_move();
+    assert!(g.delete_variation());
+    assert_eq!(g.line_len(), 0);
+    assert!(g.get_tree().children.is_empty());
+}
+
+#[test]
+fn comments_and_nags() {
+    let mut g = branched();
+    g.set_comment("covers the corner");
+    g.add_nag(NAG_GOOD);
+    g.add_nag(NAG_BLUNDER);
+    g.add_nag(NAG_GOOD);
+    assert_eq!(g.get_nags(), [NAG_GOOD, NAG_BLUNDER]);
+    g.remove_nag(NAG_GOOD);
+    g.undo_move();
+    assert_eq!(g.get_comment(), "");
+    assert!(g.get_nags().is_empty());
+    g.set_comment("center first");
+
+    let mut loaded = GameBoard::from_record(g.to_record(), false).unwrap();
+    assert_eq!(loaded.get_comment(), "center first");
+    loaded.redo_move();
+    assert_eq!(loaded.get_comment(), "covers the corner");
+    assert_eq!(loaded.get_nags(), [NAG_BLUNDER]);
+}
+
+// Record of `g` with `m` added as a variation after the first move
+fn with_variation(g: &GameBoard, m: Move) -> Record {
+    let mut record = g.to_record();
+    record.tree.children[0].children.push(Node::new(m));
+    return record;
+}
+
+#[test]
+fn illegal_records_are_refused() {
+    let g = branched();
+    assert!(GameBoard::from_record(g.to_record(), false).is_some());
+    // Onto a piece of the same size
+    let record = with_variation(&g, new_move(1, 4));
+    assert!(GameBoard::from_record(record, false).is_none());
+    // Moving the opponent's piece
+    let record = with_variation(
+        &g,
+        Move {
+            is_new: false,
+            size: 1,
+            from: 4,
+            to: 2,
+        },
+    );
+    assert!(GameBoard::from_record(record, false).is_none());
+    let mut record = g.to_record();
+    record.tree.m = Some(new_move(0, 0));
+    assert!(GameBoard::from_record(record, false).is_none());
+    let mut record = g.to_record();
+    record.line = vec![0, 2];
+    assert!(GameBoard::from_record(record, false).is_none());
+}
+
+// Both players shuttle a big piece back and forth far longer than any
+// recursion over the tree could go
+#[test]
+fn long_records_load() {
+    let plies = 20_000;
+    let mut moves = vec![new_move(2, 0), new_move(2, 8)];
+    for i in 2..plies {
+        let (a, b) = match i % 2 {
+            0 => (0, 1),
+            _ => (8, 7),
+        };
+        moves.push(match (i / 2) % 2 {
+            1 => board_move(a, b),
+            _ => board_move(b, a),
+        });
+    }
+    let mut tree = Node::root();
+    for &m in moves.iter().rev() {
+        let mut node = Node::new(m);
+        node.children = std::mem::take(&mut tree.children);
+        tree.children.push(node);
+    }
+    let mut record = GameBoard::new(false).to_record();
+    record.tree = tree;
+    record.line = vec![0; plies];
+    record.ply = plies;
+
+    let bytes = bincode::serialize(&record).unwrap();
+    let loaded: Record = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(loaded, record);
+    let g = GameBoard::from_record(loaded, false).unwrap();
+    assert_eq!(g.ply(), plies);
+    assert_eq!(g.get_history(), &moves[..]);
+}
@@ -0,0 +1,40 @@
+use gobblers::{
+    perft::perft,
+    reference::{differential, RefBoard},
+    Board,
+};
+
+#[test]
+fn board_matches_reference() {
+    for seed in 0..8 {
+        if let Err(d) = differential(seed, 200, 60) {
+            panic!("seed {}: {}", seed, d);
+        }
+    }
+}
+
+fn ref_perft(r: &mut RefBoard, depth: i32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    if r.state() != 0 {
+        return 0;
+    }
+    let mut nodes = 0;
+    for m in r.moves() {
+        r.do_move(m);
+        nodes += ref_perft(r, depth - 1);
+        r.undo_move(m);
+    }
+    return nodes;
+}
+
+#[test]
+fn perft_matches_reference() {
+    for depth in 0..4 {
+        assert_eq!(
+            perft(&Board::new(), depth),
+            ref_perft(&mut RefBoard::new(), depth)
+        );
+    }
+}
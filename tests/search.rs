@@ -0,0 +1,32 @@
+use gobblers::{rng::Rng, search::Search, GameBoard};
+
+// Positions a few random plies into the game
+fn positions() -> Vec<GameBoard> {
+    let mut rng = Rng::new(26);
+    let mut positions = Vec::new();
+    while positions.len() < 20 {
+        let mut g = GameBoard::new(false);
+        for _ in 0..2 + rng.below(6) {
+            let moves = g.get_moves();
+            if moves.is_empty() {
+                break;
+            }
+            g.do_move(moves[rng.below(moves.len())]);
+        }
+        if !g.get_moves().is_empty() {
+            positions.push(g);
+        }
+    }
+    return positions;
+}
+
+#[test]
+fn threads_agree_with_one_thread() {
+    for g in positions() {
+        let single = Search::empty().evaluate(&g, 5);
+        let mut parallel = Search::empty();
+        parallel.set_threads(4);
+        let e = parallel.evaluate(&g, 5);
+        assert_eq!((e.kind, e.depth), (single.kind, single.depth), "{:?}", g);
+    }
+}
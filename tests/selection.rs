@@ -0,0 +1,79 @@
+use gobblers::{GameBoard, Move, Rejection, SelectionEvent};
+
+mod common;
+
+use common::new_move;
+
+#[test]
+fn pick_drop_and_submit() {
+    let mut g = GameBoard::new(false);
+    assert_eq!(g.select_new(1), SelectionEvent::PickedUp(new_move(1, -1)));
+    assert_eq!(g.get_select_targets(), 0b111111111);
+    assert_eq!(g.select_board(4), SelectionEvent::Dropped(new_move(1, 4)));
+    assert_eq!(g.submit_select(), SelectionEvent::Committed(new_move(1, 4)));
+    assert_eq!(g.get_history(), &[new_move(1, 4)]);
+}
+
+#[test]
+fn auto_select_commits() {
+    let mut g = GameBoard::new(true);
+    g.select_new(2);
+    assert_eq!(g.select_board(0), SelectionEvent::Committed(new_move(2, 0)));
+}
+
+#[test]
+fn rejections_keep_the_selection() {
+    let mut g = GameBoard::new(true);
+    g.do_move(new_move(1, 4));
+    assert_eq!(
+        g.select_board(4),
+        SelectionEvent::Invalid(Rejection::NotYourPiece)
+    );
+    assert_eq!(g.select_board(0), SelectionEvent::Invalid(Rejection::Empty));
+    assert_eq!(
+        g.select_board(9),
+        SelectionEvent::Invalid(Rejection::OffBoard)
+    );
+    g.select_new(0);
+    assert_eq!(g.get_select_targets(), 0b111101111);
+    assert_eq!(
+        g.select_board(4),
+        SelectionEvent::Invalid(Rejection::Covered)
+    );
+    assert!(g.is_selected_new(0));
+    assert_eq!(g.select_new(0), SelectionEvent::Deselected);
+    assert_eq!(
+        g.remove_select(),
+        SelectionEvent::Invalid(Rejection::NothingSelected)
+    );
+    assert_eq!(
+        g.submit_select(),
+        SelectionEvent::Invalid(Rejection::NothingDropped)
+    );
+}
+
+#[test]
+fn board_pieces() {
+    let mut g = GameBoard::new(false);
+    g.do_move(new_move(0, 0));
+    g.do_move(new_move(0, 8));
+    g.do_move(new_move(2, 4));
+    g.do_move(new_move(0, 2));
+    let picked = Move {
+        is_new: false,
+        size: 2,
+        from: 4,
+        to: -1,
+    };
+    assert_eq!(g.select_board(4), SelectionEvent::PickedUp(picked));
+    assert_eq!(g.get_select_targets(), 0b111101111);
+    assert_eq!(g.select_board(4), SelectionEvent::Deselected);
+    // Picking another own piece switches to it when it can't be covered
+    assert!(matches!(g.select_board(0), SelectionEvent::PickedUp(m) if m.from == 0));
+    assert_eq!(g.get_select_targets(), 0b011101010);
+    assert_eq!(g.select_board(4), SelectionEvent::PickedUp(picked));
+    assert_eq!(
+        g.select_board(0),
+        SelectionEvent::Dropped(Move { to: 0, ..picked })
+    );
+}
@@ -0,0 +1,91 @@
+use gobblers::{
+    reference::check_game_board,
+    rng::Rng,
+    search::{EvalKind, Search},
+    setup::{Setup, SetupError},
+    GameBoard, State,
+};
+
+// Player 1 to move, player 0 threatens the top row
+fn puzzle() -> Setup {
+    let mut s = Setup::new();
+    s.place(0, 2, 0).unwrap();
+    s.place(0, 2, 1).unwrap();
+    s.place(1, 0, 4).unwrap();
+    s.place(0, 1, 4).unwrap();
+    s.set_player(1).unwrap();
+    return s;
+}
+
+#[test]
+fn edit_and_play() {
+    let mut g = puzzle().finish(false).unwrap();
+    assert_eq!(g.player(), 1);
+    assert_eq!(g.get_top(4), Some((0, 1)));
+    assert_eq!(g.get_left(0, 2), 0);
+    assert!(g.get_history().is_empty());
+    let m = g.get_moves()[0];
+    assert!(g.do_move(m));
+    assert_eq!(g.get_history(), &[m]);
+    g.goto_first();
+    assert_eq!(g.get_board(), g.get_start());
+    let loaded = GameBoard::from_record(g.to_record(), false).unwrap();
+    assert_eq!(loaded.get_start(), g.get_start());
+}
+
+#[test]
+fn edits_are_checked() {
+    let mut s = puzzle();
+    assert_eq!(s.place(1, 2, 0), Err(SetupError::Taken));
+    assert_eq!(s.place(0, 2, 5), Err(SetupError::NoneLeft));
+    assert_eq!(s.place(0, 3, 5), Err(SetupError::OffBoard));
+    assert_eq!(s.remove(1, 8), Err(SetupError::Empty));
+    assert_eq!(s.set_reserve(0, 2, 1), Err(SetupError::TooMany));
+    assert_eq!(s.remove(2, 1), Ok(0));
+    assert_eq!(s.get_board().pieces[2], 1);
+    s.place(0, 0, 2).unwrap();
+    s.place(0, 2, 1).unwrap();
+    assert_eq!(s.finish(false), Err(SetupError::GameOver));
+    s.clear();
+    assert_eq!(s.get_board().player, 1);
+    assert_eq!(s.get_piece(2, 0), None);
+}
+
+#[test]
+fn games_from_setups_follow_the_rules() {
+    let mut rng = Rng::new(3);
+    for _ in 0..50 {
+        let mut g = puzzle().finish(false).unwrap();
+        while g.get_state() == State::InGame && g.ply() < 40 {
+            let moves = g.get_moves();
+            g.do_move(moves[rng.below(moves.len())]);
+            if let Some(what) = check_game_board(&g) {
+                panic!("{} after {:?}", what, g.get_history());
+            }
+        }
+    }
+}
+
+#[test]
+fn pieces_out_of_play_are_searched_apart() {
+    // Player 1 completes the top row by covering the small piece on 2
+    let mut s = Setup::new();
+    s.place(1, 2, 0).unwrap();
+    s.place(1, 2, 1).unwrap();
+    s.place(0, 0, 2).unwrap();
+    s.place(0, 1, 4).unwrap();
+    s.set_player(1).unwrap();
+    let mut search = Search::empty();
+    assert_eq!(search.evaluate_board(s.get_board(), 4).kind, EvalKind::Win);
+    let cached = search.cache_stats().entries;
+    // Not without medium pieces, although the id is the same
+    s.set_reserve(1, 1, 0).unwrap();
+    assert_eq!(
+        search.evaluate_board(s.get_board(), 4).kind,
+        EvalKind::TooFar
+    );
+    let mut g = s.finish(false).unwrap();
+    g.set_advisor(search.clone(), 4);
+    assert_eq!(g.hint().unwrap().kind, EvalKind::TooFar);
+    assert_eq!(search.cache_stats().entries, cached);
+}
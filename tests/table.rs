@@ -0,0 +1,93 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+};
+
+use gobblers::{
+    search::table::{Entry, Table, BOUND_EXACT, BOUND_LOWER, BOUND_UPPER},
+    Board,
+};
+
+// A key other than `key` that lands in the same slot of a table of `bits`
+fn colliding(bits: u32, key: u64) -> u64 {
+    let table = Table::new(bits);
+    let mut other = key + 1;
+    loop {
+        table.store(key, entry(1));
+        if table.probe(other).is_none() {
+            table.store(other, entry(1));
+            if table.probe(key).is_none() {
+                return other;
+            }
+        }
+        other += 1;
+    }
+}
+
+fn entry(score: i32) -> Entry {
+    return Entry {
+        score,
+        depth: 7,
+        bound: BOUND_EXACT,
+    };
+}
+
+#[test]
+fn stored_entries_probe_back() {
+    let table = Table::new(10);
+    let key = Table::key(&Board::new());
+    assert_eq!(table.probe(key), None);
+    for (score, bound) in [(1003, BOUND_LOWER), (-1005, BOUND_UPPER), (0, BOUND_EXACT)] {
+        let e = Entry {
+            score,
+            depth: 12,
+            bound,
+        };
+        table.store(key, e);
+        assert_eq!(table.probe(key), Some(e));
+    }
+}
+
+#[test]
+fn foreign_slot_is_rejected() {
+    let table = Table::new(1);
+    let key = 42;
+    let other = colliding(1, key);
+    table.store(key, entry(1001));
+    assert_eq!(table.probe(other), None);
+    assert_eq!(table.probe(key), Some(entry(1001)));
+}
+
+// Two writers race on one slot, readers must never see one key's check
+// word with the other entry's data
+#[test]
+fn torn_slot_is_rejected() {
+    let table = Table::new(1);
+    let a = 42;
+    let b = colliding(1, a);
+    let done = AtomicBool::new(false);
+    thread::scope(|s| {
+        for (key, score) in [(a, 1001), (b, -1002)] {
+            let table = &table;
+            let done = &done;
+            s.spawn(move || {
+                for i in 0..200_000 {
+                    let e = Entry {
+                        score,
+                        depth: i % 100,
+                        bound: BOUND_EXACT,
+                    };
+                    table.store(key, e);
+                }
+                done.store(true, Ordering::Relaxed);
+            });
+        }
+        while !done.load(Ordering::Relaxed) {
+            for (key, score) in [(a, 1001), (b, -1002)] {
+                if let Some(e) = table.probe(key) {
+                    assert_eq!(e.score, score);
+                }
+            }
+        }
+    });
+}